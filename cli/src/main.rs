@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Context, Result};
 use clap::Parser;
 use std::fs::read_to_string;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tree_painter::{Lang, Renderer, Theme};
 
 #[derive(Parser)]
@@ -17,38 +17,27 @@ struct Args {
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let lang = Lang::from(&args.source)
-        .ok_or_else(|| anyhow!("Cannot determine language from file extension"))?;
+    let source = read_to_string(&args.source).context("Loading sources")?;
+    let lang = Lang::detect(&args.source, Some(source.as_bytes()))
+        .into_iter()
+        .next()
+        .map(|guess| guess.lang)
+        .ok_or_else(|| anyhow!("Cannot determine language of source file"))?;
+
+    let theme = Theme::from_helix_dir(
+        &read_to_string(&args.theme).context("Loading theme")?,
+        args.theme.parent().unwrap_or(Path::new(".")),
+    )?;
+    let title = args
+        .source
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
 
-    let source = read_to_string(args.source).context("Loading sources")?;
-    let theme = Theme::from_helix(&read_to_string(args.theme).context("Loading theme")?)?;
     let mut renderer = Renderer::new(theme);
-
-    print!(
-        r#"
-    <!DOCTYPE html>
-      <title>tree-painter highlighting</title>
-      <style>
-        {}
-      </style>
-    </head>
-    <body class="tsc-bg">
-      <pre>
-        <table>
-          <tbody>"#,
-        renderer.css()
-    );
-
-    for line in renderer.render(&lang, source.as_bytes())? {
-        print!(r#"<tr><td class="tsc-line">{line}</td></tr>"#);
-    }
-
     print!(
-        r#"
-          </tbody>
-        </table>
-      </pre>
-    </body>"#
+        "{}",
+        renderer.render_document(&lang, source.as_bytes(), &title)?
     );
 
     Ok(())
@@ -0,0 +1,297 @@
+use crate::color::Rgb;
+use crate::theme::{Style, UnderlineStyle};
+use tree_sitter_highlight::{Highlight, HighlightEvent};
+
+const RESET: &str = "\x1b[0m";
+
+/// Levels of a channel in the 6x6x6 color cube of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// SGR foreground codes of red, yellow, green, cyan, blue and magenta ordered by hue.
+const HUE_CODES: [u8; 6] = [31, 33, 32, 36, 34, 35];
+
+/// Color capabilities of a terminal. Theme colors are downsampled to the closest color available.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit RGB colors.
+    TrueColor,
+    /// The 256 colors of the xterm palette.
+    Ansi256,
+    /// The 16 basic ANSI colors.
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Guess the color depth of the current terminal from the `COLORTERM` and `TERM` environment
+    /// variables.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorDepth::TrueColor;
+        }
+
+        if std::env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// Index of the closest color in the 256-color palette, ignoring the basic colors 0 to 15 whose
+/// actual values depend on the terminal.
+fn ansi256(color: Rgb) -> u8 {
+    let level = |c: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i32 - c as i32).abs())
+            .map_or(0, |(index, _)| index as u8)
+    };
+
+    let (r, g, b) = (level(color.r), level(color.g), level(color.b));
+    let cube = Rgb::new(
+        CUBE_LEVELS[r as usize],
+        CUBE_LEVELS[g as usize],
+        CUBE_LEVELS[b as usize],
+    );
+
+    let average = (color.r as u32 + color.g as u32 + color.b as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+    let gray = Rgb::new(gray_level, gray_level, gray_level);
+
+    if color.distance(&gray) < color.distance(&cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * r + 6 * g + b
+    }
+}
+
+/// SGR code of the basic ANSI color closest in hue and brightness.
+///
+/// A plain euclidean distance would map most of the pastel colors of popular themes to white, so
+/// grayish colors are mapped by brightness and all others by their hue.
+fn ansi16(color: Rgb) -> u8 {
+    let max = color.r.max(color.g).max(color.b) as i32;
+    let min = color.r.min(color.g).min(color.b) as i32;
+    let chroma = max - min;
+
+    if chroma < 32 {
+        return match max {
+            0..=63 => 30,
+            64..=159 => 90,
+            160..=223 => 37,
+            _ => 97,
+        };
+    }
+
+    let (r, g, b) = (color.r as i32, color.g as i32, color.b as i32);
+
+    let hue = if max == r {
+        60 * (g - b) / chroma
+    } else if max == g {
+        60 * (b - r) / chroma + 120
+    } else {
+        60 * (r - g) / chroma + 240
+    };
+
+    let code = HUE_CODES[((hue + 390) / 60 % 6) as usize];
+
+    if max > 191 {
+        code + 60
+    } else {
+        code
+    }
+}
+
+/// SGR parameters selecting `color` as foreground, background or underline color, which differ
+/// by their `base` of 30, 40 or 50 respectively.
+fn color_params(color: Rgb, base: u8, depth: ColorDepth) -> String {
+    let extended = base + 8;
+
+    match depth {
+        ColorDepth::TrueColor => format!("{extended};2;{};{};{}", color.r, color.g, color.b),
+        ColorDepth::Ansi256 => format!("{extended};5;{}", ansi256(color)),
+        // There are no basic underline colors, fall back to the default underline color.
+        ColorDepth::Ansi16 if base == 50 => String::new(),
+        ColorDepth::Ansi16 => (ansi16(color) - 30 + base).to_string(),
+    }
+}
+
+/// Escape sequence enabling `style` or an empty string if `style` does not change anything.
+fn escape(style: &Style, depth: ColorDepth) -> String {
+    let mut params = Vec::new();
+
+    let modifiers = [
+        (style.is_bold, "1"),
+        (style.is_dim, "2"),
+        (style.is_italic, "3"),
+        (style.is_slow_blink, "5"),
+        (style.is_rapid_blink, "6"),
+        (style.is_reversed, "7"),
+        (style.is_hidden, "8"),
+        (style.is_crossed_out, "9"),
+    ];
+
+    for (enabled, code) in modifiers {
+        if enabled {
+            params.push(code.to_string());
+        }
+    }
+
+    if style.is_underlined {
+        params.push(
+            match style.underline_style {
+                UnderlineStyle::Line => "4",
+                UnderlineStyle::DoubleLine => "4:2",
+                UnderlineStyle::Curl => "4:3",
+                UnderlineStyle::Dotted => "4:4",
+                UnderlineStyle::Dashed => "4:5",
+            }
+            .to_string(),
+        );
+
+        if let Some(color) = style.underline_color.as_deref().and_then(Rgb::from_hex) {
+            params.push(color_params(color, 50, depth));
+        }
+    }
+
+    if let Some(color) = style.color.as_deref().and_then(Rgb::from_hex) {
+        params.push(color_params(color, 30, depth));
+    }
+
+    if let Some(color) = style.background.as_deref().and_then(Rgb::from_hex) {
+        params.push(color_params(color, 40, depth));
+    }
+
+    params.retain(|param| !param.is_empty());
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("\x1b[{}m", params.join(";"))
+    }
+}
+
+/// Escape sequence of the innermost highlight in `stack` that has a style.
+fn innermost(stack: &[Option<&Style>], depth: ColorDepth) -> String {
+    stack
+        .iter()
+        .rev()
+        .find_map(|style| *style)
+        .map(|style| escape(style, depth))
+        .unwrap_or_default()
+}
+
+/// Render highlight `events` of `source` as text with ANSI escape sequences.
+///
+/// Styles are reset at the end of each line and re-enabled at the start of the next one, so that
+/// the output can be processed line by line.
+pub(crate) fn render<'a>(
+    events: &[HighlightEvent],
+    source: &[u8],
+    style: impl Fn(Highlight) -> Option<&'a Style>,
+    depth: ColorDepth,
+) -> String {
+    let mut output = String::with_capacity(source.len() * 2);
+    let mut stack = Vec::new();
+    let mut current = String::new();
+
+    for event in events {
+        let next = match *event {
+            HighlightEvent::HighlightStart(highlight) => {
+                stack.push(style(highlight));
+                innermost(&stack, depth)
+            }
+            HighlightEvent::HighlightEnd => {
+                stack.pop();
+                innermost(&stack, depth)
+            }
+            HighlightEvent::Source { start, end } => {
+                let text = String::from_utf8_lossy(&source[start..end]);
+
+                for (index, line) in text.split('\n').enumerate() {
+                    if index > 0 {
+                        if !current.is_empty() {
+                            output.push_str(RESET);
+                        }
+
+                        output.push('\n');
+                        output.push_str(&current);
+                    }
+
+                    output.push_str(line);
+                }
+
+                continue;
+            }
+        };
+
+        if next != current {
+            if !current.is_empty() {
+                output.push_str(RESET);
+            }
+
+            output.push_str(&next);
+            current = next;
+        }
+    }
+
+    if !current.is_empty() {
+        output.push_str(RESET);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(color: &str) -> Rgb {
+        Rgb::from_hex(color).unwrap()
+    }
+
+    #[test]
+    fn ansi256_maps_grays_to_gray_ramp() {
+        assert_eq!(ansi256(hex("#080808")), 232);
+        assert_eq!(ansi256(hex("#808080")), 244);
+        assert_eq!(ansi256(hex("#eeeeee")), 255);
+    }
+
+    #[test]
+    fn ansi256_maps_cube_corners() {
+        assert_eq!(ansi256(hex("#000000")), 16);
+        assert_eq!(ansi256(hex("#ff0000")), 196);
+        assert_eq!(ansi256(hex("#00ff00")), 46);
+        assert_eq!(ansi256(hex("#0000ff")), 21);
+        assert_eq!(ansi256(hex("#ffff00")), 226);
+        assert_eq!(ansi256(hex("#00ffff")), 51);
+        assert_eq!(ansi256(hex("#ff00ff")), 201);
+        assert_eq!(ansi256(hex("#ffffff")), 231);
+    }
+
+    #[test]
+    fn ansi16_maps_grays_by_brightness() {
+        assert_eq!(ansi16(hex("#000000")), 30);
+        assert_eq!(ansi16(hex("#808080")), 90);
+        assert_eq!(ansi16(hex("#c0c0c0")), 37);
+        assert_eq!(ansi16(hex("#ffffff")), 97);
+    }
+
+    #[test]
+    fn ansi16_maps_colors_by_hue() {
+        assert_eq!(ansi16(hex("#aa0000")), 31);
+        assert_eq!(ansi16(hex("#ff0000")), 91);
+        assert_eq!(ansi16(hex("#00ff00")), 92);
+        assert_eq!(ansi16(hex("#ffff00")), 93);
+        assert_eq!(ansi16(hex("#0000aa")), 34);
+        assert_eq!(ansi16(hex("#00aaaa")), 36);
+        assert_eq!(ansi16(hex("#aa00aa")), 35);
+        // Pastel colors of dark themes keep their hue instead of turning white.
+        assert_eq!(ansi16(hex("#f38ba8")), 91);
+        assert_eq!(ansi16(hex("#a6e3a1")), 92);
+    }
+}
@@ -0,0 +1,172 @@
+use crate::color::Rgb;
+use crate::{Error, Style, Theme};
+use serde_yaml::Value;
+
+/// Slots of base16 schemes, base24 schemes add `base10` to `base17`.
+const SLOTS: [&str; 24] = [
+    "base00", "base01", "base02", "base03", "base04", "base05", "base06", "base07", "base08",
+    "base09", "base0A", "base0B", "base0C", "base0D", "base0E", "base0F", "base10", "base11",
+    "base12", "base13", "base14", "base15", "base16", "base17",
+];
+
+/// Number of slots required by base16 schemes.
+const BASE16_SLOTS: usize = 16;
+
+/// Capture names, their slot and whether they are bold, italic or underlined.
+const STYLES: &[(&str, &str, Modifier)] = &[
+    ("attribute", "base09", Modifier::Plain),
+    ("comment", "base03", Modifier::Italic),
+    ("constant", "base09", Modifier::Plain),
+    ("constant.character.escape", "base0C", Modifier::Plain),
+    ("constructor", "base0D", Modifier::Plain),
+    ("function", "base0D", Modifier::Plain),
+    ("function.builtin", "base0C", Modifier::Plain),
+    ("keyword", "base0E", Modifier::Plain),
+    ("label", "base0E", Modifier::Plain),
+    ("namespace", "base0E", Modifier::Plain),
+    ("operator", "base05", Modifier::Plain),
+    ("punctuation", "base05", Modifier::Plain),
+    ("special", "base0F", Modifier::Plain),
+    ("string", "base0B", Modifier::Plain),
+    ("string.regexp", "base0C", Modifier::Plain),
+    ("tag", "base08", Modifier::Plain),
+    ("type", "base0A", Modifier::Plain),
+    ("type.builtin", "base0C", Modifier::Plain),
+    ("variable", "base08", Modifier::Plain),
+    ("variable.builtin", "base09", Modifier::Plain),
+    ("variable.other.member", "base08", Modifier::Plain),
+    ("variable.parameter", "base08", Modifier::Plain),
+    ("markup.bold", "base0A", Modifier::Bold),
+    ("markup.heading", "base0D", Modifier::Plain),
+    ("markup.italic", "base0E", Modifier::Italic),
+    ("markup.link.text", "base08", Modifier::Plain),
+    ("markup.link.url", "base09", Modifier::Underlined),
+    ("markup.list", "base08", Modifier::Plain),
+    ("markup.quote", "base0C", Modifier::Plain),
+    ("markup.raw", "base0B", Modifier::Plain),
+    ("diff.delta", "base0E", Modifier::Plain),
+    ("diff.minus", "base08", Modifier::Plain),
+    ("diff.plus", "base0B", Modifier::Plain),
+];
+
+/// Modifier of a base16 style.
+#[derive(Clone, Copy, PartialEq)]
+enum Modifier {
+    Plain,
+    Bold,
+    Italic,
+    Underlined,
+}
+
+impl Theme {
+    /// Load theme from a [base16](https://github.com/tinted-theming/home) or base24 scheme stored
+    /// in `data` as YAML, either with the `baseXX` slots at the top level or below `palette`.
+    ///
+    /// The slots are available as palette entries of the theme. `base00` is the background,
+    /// `base05` the foreground and capture names are styled following the base16 styling
+    /// guidelines, which base24 schemes share:
+    ///
+    /// | Slot | Capture names |
+    /// | --- | --- |
+    /// | `base03` | `comment` (italic) |
+    /// | `base05` | `operator`, `punctuation` |
+    /// | `base08` | `variable`, `variable.other.member`, `variable.parameter`, `tag`, `markup.link.text`, `markup.list`, `diff.minus` |
+    /// | `base09` | `constant`, `attribute`, `variable.builtin`, `markup.link.url` (underlined) |
+    /// | `base0A` | `type`, `markup.bold` (bold) |
+    /// | `base0B` | `string`, `markup.raw`, `diff.plus` |
+    /// | `base0C` | `constant.character.escape`, `string.regexp`, `function.builtin`, `type.builtin`, `markup.quote` |
+    /// | `base0D` | `function`, `constructor`, `markup.heading` |
+    /// | `base0E` | `keyword`, `label`, `namespace`, `markup.italic` (italic), `diff.delta` |
+    /// | `base0F` | `special` |
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Yaml`] if `data` is not valid YAML, [`Error::InvalidTheme`] if one of the
+    /// slots `base00` to `base0F` is missing and [`Error::InvalidColor`] naming the slot if it is
+    /// not a hex color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = r#"
+    /// scheme: "Default Dark"
+    /// author: "Chris Kempson"
+    /// base00: "181818"
+    /// base01: "282828"
+    /// base02: "383838"
+    /// base03: "585858"
+    /// base04: "b8b8b8"
+    /// base05: "d8d8d8"
+    /// base06: "e8e8e8"
+    /// base07: "f8f8f8"
+    /// base08: "ab4642"
+    /// base09: "dc9656"
+    /// base0A: "f7ca88"
+    /// base0B: "a1b56c"
+    /// base0C: "86c1b9"
+    /// base0D: "7cafc2"
+    /// base0E: "ba8baf"
+    /// base0F: "a16946"
+    /// "#;
+    ///
+    /// let theme = tree_painter::Theme::from_base16(data).unwrap();
+    ///
+    /// assert_eq!(theme.background(), "#181818");
+    /// assert_eq!(theme.palette_color("base0E"), Some("#ba8baf"));
+    /// assert_eq!(theme.resolve("keyword.control").unwrap().color.as_deref(), Some("#ba8baf"));
+    /// ```
+    pub fn from_base16(data: &str) -> Result<Self, Error> {
+        let root = serde_yaml::from_str::<Value>(data)?;
+        let colors = root.get("palette").unwrap_or(&root);
+
+        let mut builder = Theme::builder().foreground("base05").background("base00");
+
+        for (index, slot) in SLOTS.iter().enumerate() {
+            let color = match colors.get(slot) {
+                Some(value) => color(slot, value)?,
+                None if index < BASE16_SLOTS => return Err(Error::InvalidTheme),
+                None => continue,
+            };
+
+            builder = builder.palette(*slot, color);
+        }
+
+        for (capture, slot, modifier) in STYLES {
+            let style = Style {
+                color: Some(slot.to_string()),
+                is_bold: *modifier == Modifier::Bold,
+                is_italic: *modifier == Modifier::Italic,
+                is_underlined: *modifier == Modifier::Underlined,
+                ..Default::default()
+            };
+
+            builder = builder.style(*capture, style);
+        }
+
+        builder.build()
+    }
+}
+
+/// Hex color of `slot` with the leading `#` that schemes usually omit.
+///
+/// Unquoted colors consisting of digits only are parsed as YAML numbers and padded again.
+fn color(slot: &str, value: &Value) -> Result<String, Error> {
+    let hex = match value {
+        Value::String(hex) => hex.trim_start_matches('#').to_string(),
+        Value::Number(number) => number
+            .as_u64()
+            .map(|number| format!("{number:06}"))
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+
+    let color = format!("#{hex}");
+
+    match Rgb::from_hex(&color) {
+        Some(_) if hex.len() == 6 => Ok(color),
+        _ => Err(Error::InvalidColor {
+            key: slot.to_string(),
+            color: hex,
+        }),
+    }
+}
@@ -0,0 +1,135 @@
+/// highlight.js classes of capture names, without the `hljs-` prefix.
+const HIGHLIGHT_JS: &[(&str, &str)] = &[
+    ("attribute", "attr"),
+    ("comment", "comment"),
+    ("constant", "variable constant_"),
+    ("constant.builtin", "literal"),
+    ("constant.character", "string"),
+    ("constant.character.escape", "char escape_"),
+    ("constant.numeric", "number"),
+    ("constructor", "title class_"),
+    ("diff.minus", "deletion"),
+    ("diff.plus", "addition"),
+    ("escape", "char escape_"),
+    ("function", "title function_"),
+    ("function.builtin", "built_in"),
+    ("include", "keyword"),
+    ("keyword", "keyword"),
+    ("keyword.directive", "meta"),
+    ("label", "symbol"),
+    ("markup.bold", "strong"),
+    ("markup.heading", "section"),
+    ("markup.italic", "emphasis"),
+    ("markup.link", "link"),
+    ("markup.list", "bullet"),
+    ("markup.quote", "quote"),
+    ("markup.raw", "code"),
+    ("namespace", "title"),
+    ("number", "number"),
+    ("operator", "operator"),
+    ("property", "property"),
+    ("punctuation", "punctuation"),
+    ("repeat", "keyword"),
+    ("string", "string"),
+    ("string.regexp", "regexp"),
+    ("string.special.symbol", "symbol"),
+    ("string.special.url", "link"),
+    ("tag", "name"),
+    ("type", "type"),
+    ("type.builtin", "built_in"),
+    ("variable", "variable"),
+    ("variable.builtin", "variable language_"),
+    ("variable.other.member", "property"),
+    ("variable.parameter", "params"),
+];
+
+/// Pygments short classes of capture names.
+const PYGMENTS: &[(&str, &str)] = &[
+    ("attribute", "na"),
+    ("comment", "c"),
+    ("comment.block", "cm"),
+    ("comment.line", "c1"),
+    ("constant", "no"),
+    ("constant.builtin", "kc"),
+    ("constant.character", "sc"),
+    ("constant.character.escape", "se"),
+    ("constant.numeric", "m"),
+    ("constant.numeric.float", "mf"),
+    ("constant.numeric.integer", "mi"),
+    ("constructor", "nc"),
+    ("diff.minus", "gd"),
+    ("diff.plus", "gi"),
+    ("escape", "se"),
+    ("function", "nf"),
+    ("function.builtin", "nb"),
+    ("include", "kn"),
+    ("keyword", "k"),
+    ("keyword.control.import", "kn"),
+    ("keyword.directive", "cp"),
+    ("keyword.function", "kd"),
+    ("keyword.operator", "ow"),
+    ("keyword.storage", "kd"),
+    ("keyword.storage.type", "kt"),
+    ("label", "nl"),
+    ("markup.bold", "gs"),
+    ("markup.heading", "gh"),
+    ("markup.italic", "ge"),
+    ("namespace", "nn"),
+    ("number", "m"),
+    ("operator", "o"),
+    ("property", "py"),
+    ("punctuation", "p"),
+    ("repeat", "k"),
+    ("string", "s"),
+    ("string.regexp", "sr"),
+    ("string.special.symbol", "ss"),
+    ("tag", "nt"),
+    ("type", "nc"),
+    ("type.builtin", "kt"),
+    ("variable", "n"),
+    ("variable.builtin", "bp"),
+    ("variable.other.member", "py"),
+];
+
+/// Naming scheme of the CSS classes of rendered HTML.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClassNames {
+    /// Capture names with dots replaced by dashes and prepended by the class prefix, e.g.
+    /// `tsc-function-method` for `function.method`.
+    #[default]
+    Captures,
+    /// Classes of [highlight.js](https://highlightjs.readthedocs.io/en/latest/css-classes-reference.html),
+    /// e.g. `hljs-title function_` for `function.method`.
+    HighlightJs,
+    /// Short classes of [Pygments](https://pygments.org/docs/tokens/), e.g. `nf` for
+    /// `function.method`.
+    Pygments,
+}
+
+impl ClassNames {
+    /// Classes for the capture `name`, separated by spaces, or [`None`] if the scheme has no
+    /// class for it.
+    ///
+    /// Unless the scheme is [`ClassNames::Captures`], several capture names share a class and
+    /// `name` maps to the class of the capture name matching most of its dotted parts.
+    pub(crate) fn class(&self, name: &str, prefix: &str) -> Option<String> {
+        let table = match self {
+            ClassNames::Captures => return Some(format!("{prefix}{}", name.replace('.', "-"))),
+            ClassNames::HighlightJs => HIGHLIGHT_JS,
+            ClassNames::Pygments => PYGMENTS,
+        };
+
+        let (_, class) = table
+            .iter()
+            .filter(|(capture, _)| {
+                name.strip_prefix(capture)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .max_by_key(|(capture, _)| capture.len())?;
+
+        match self {
+            ClassNames::HighlightJs => Some(format!("hljs-{class}")),
+            _ => Some(class.to_string()),
+        }
+    }
+}
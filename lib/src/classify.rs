@@ -0,0 +1,419 @@
+use crate::{Lang, INFOS};
+use tree_sitter::{Node, Parser};
+
+/// Maximum number of leading source bytes considered for classification.
+const MAX_BYTES: usize = 16 * 1024;
+
+/// Share of the score that does not depend on the number of keyword hits, so that a clean parse
+/// with a single hit still counts for something.
+const PARSE_WEIGHT: f32 = 0.25;
+
+/// A language guessed from source code by [`Lang::classify()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Classification {
+    /// Guessed language.
+    pub lang: Lang,
+    /// Confidence between 0 and 1.
+    pub confidence: f32,
+}
+
+impl Lang {
+    /// Guess the language of `source` without any file name, for example a pasted snippet or an
+    /// untagged Markdown code fence, among all languages enabled by feature flags.
+    ///
+    /// Each candidate grammar parses the source and is scored by the share of bytes that parse
+    /// without errors as well as by typical keywords and idioms found in the source. Candidates
+    /// without any of their keywords in the source do not score, so [`None`] is returned for
+    /// empty sources and text that does not look like code of any enabled language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let source = b"fn main() {\n    let mut x = 1;\n    println!(\"{x}\");\n}\n";
+    /// let guess = tree_painter::Lang::classify(source).unwrap();
+    /// assert_eq!(guess.lang, tree_painter::Lang::Rust);
+    /// assert!(guess.confidence > 0.5);
+    ///
+    /// assert_eq!(tree_painter::Lang::classify(b"hello world"), None);
+    /// ```
+    pub fn classify(source: &[u8]) -> Option<Classification> {
+        let candidates = INFOS.iter().filter_map(|info| Lang::from_name(info.id));
+        Self::classify_among(source, candidates).into_iter().next()
+    }
+
+    /// Score `source` for each of the `candidates`, which may include dynamically loaded
+    /// grammars, and return the results ordered from the best to the worst match, omitting
+    /// candidates without any score.
+    ///
+    /// Dynamically loaded grammars have no known keywords and are scored by parsing alone, with
+    /// at most the confidence of a clean parse without keyword hits.
+    pub fn classify_among<I: IntoIterator<Item = Lang>>(
+        source: &[u8],
+        candidates: I,
+    ) -> Vec<Classification> {
+        let source = truncate(source);
+
+        if source.iter().all(u8::is_ascii_whitespace) {
+            return Vec::new();
+        }
+
+        let text = String::from_utf8_lossy(source);
+        let mut parser = Parser::new();
+
+        let mut scores = candidates
+            .into_iter()
+            .filter_map(|lang| {
+                parser.set_language(lang.language()).ok()?;
+                let tree = parser.parse(source, None)?;
+                let valid = 1.0 - error_bytes(tree.root_node()) as f32 / source.len() as f32;
+
+                let keywords = lang.keywords();
+                let hits = keywords
+                    .iter()
+                    .filter(|keyword| text.contains(*keyword))
+                    .count() as f32;
+
+                if hits == 0.0 && !keywords.is_empty() {
+                    return None;
+                }
+
+                let confidence =
+                    valid.max(0.0) * (PARSE_WEIGHT + (1.0 - PARSE_WEIGHT) * hits / (hits + 2.0));
+
+                (confidence > 0.0).then_some(Classification { lang, confidence })
+            })
+            .collect::<Vec<_>>();
+
+        scores.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        scores
+    }
+
+    /// Keywords and idioms that are characteristic for the language.
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            #[cfg(feature = "tree-sitter-c")]
+            Lang::C => &[
+                "#include <",
+                "int main(",
+                "printf(",
+                "sizeof(",
+                "typedef struct",
+                "NULL",
+                "->",
+            ],
+            #[cfg(feature = "tree-sitter-cpp")]
+            Lang::Cpp => &[
+                "#include <",
+                "std::",
+                "template <",
+                "nullptr",
+                "namespace ",
+                "public:",
+                "cout <<",
+            ],
+            #[cfg(feature = "tree-sitter-c-sharp")]
+            Lang::CSharp => &[
+                "using System",
+                "namespace ",
+                "public class ",
+                "Console.Write",
+                "{ get;",
+                "async Task",
+                "var ",
+            ],
+            #[cfg(feature = "tree-sitter-css")]
+            Lang::Css => &[
+                "color:",
+                "margin:",
+                "padding:",
+                "display:",
+                "px;",
+                "@media",
+                "!important",
+            ],
+            #[cfg(feature = "tree-sitter-dockerfile")]
+            Lang::Docker => &[
+                "FROM ",
+                "RUN ",
+                "COPY ",
+                "WORKDIR ",
+                "ENTRYPOINT ",
+                "EXPOSE ",
+                "CMD [",
+            ],
+            #[cfg(feature = "tree-sitter-go")]
+            Lang::Go => &[
+                "package ", "func ", ":= ", "fmt.", "import (", "go func", "chan ",
+            ],
+            #[cfg(feature = "tree-sitter-haskell")]
+            Lang::Haskell => &[
+                " :: ",
+                "module ",
+                "import qualified",
+                "<$>",
+                " >>= ",
+                "deriving (",
+                "instance ",
+            ],
+            #[cfg(feature = "tree-sitter-java")]
+            Lang::Java => &[
+                "public static void main",
+                "System.out.",
+                "import java.",
+                "public class ",
+                "@Override",
+                "private final ",
+                "new ",
+            ],
+            #[cfg(feature = "tree-sitter-javascript")]
+            Lang::Js => &[
+                "function ",
+                "const ",
+                "=> ",
+                "console.log",
+                "require(",
+                "document.",
+                "export default",
+            ],
+            #[cfg(feature = "tree-sitter-json")]
+            Lang::Json => &[
+                "\": \"", "\": {", "\": [", "\": true", "\": null", "},", "],",
+            ],
+            #[cfg(feature = "tree-sitter-kotlin")]
+            Lang::Kotlin => &[
+                "fun ",
+                "val ",
+                "println(",
+                "data class ",
+                "companion object",
+                "?.let",
+                "override fun",
+            ],
+            #[cfg(feature = "tree-sitter-latex")]
+            Lang::Latex => &[
+                "\\begin{",
+                "\\end{",
+                "\\documentclass",
+                "\\usepackage",
+                "\\section",
+                "\\textbf{",
+                "$$",
+            ],
+            #[cfg(feature = "tree-sitter-lua")]
+            Lang::Lua => &[
+                "local ",
+                "function ",
+                "end\n",
+                " then\n",
+                "elseif ",
+                " ~= ",
+                " .. ",
+            ],
+            #[cfg(feature = "tree-sitter-md")]
+            Lang::Markdown => &["# ", "## ", "```", "* ", "- [", "](http", "**"],
+            #[cfg(feature = "tree-sitter-nix")]
+            Lang::Nix => &[
+                "{ pkgs",
+                "pkgs.",
+                "mkDerivation",
+                "inherit ",
+                "import <nixpkgs>",
+                "let\n",
+                "in\n",
+            ],
+            #[cfg(feature = "tree-sitter-ocaml")]
+            Lang::Ocaml => &[
+                "let rec ", "match ", " with\n", "let () =", "module ", ";;", "fun ",
+            ],
+            #[cfg(feature = "tree-sitter-python")]
+            Lang::Python => &[
+                "def ", "import ", "self.", "elif ", "print(", "__init__", "None",
+            ],
+            #[cfg(feature = "tree-sitter-rust")]
+            Lang::Rust => &[
+                "fn ", "let mut ", "impl ", "pub fn ", "println!", "&self", "#[derive",
+            ],
+            #[cfg(feature = "tree-sitter-typescript")]
+            Lang::Ts => &[
+                ": string",
+                ": number",
+                "interface ",
+                "export ",
+                "=> ",
+                "type ",
+                "readonly ",
+            ],
+            #[cfg(feature = "tree-sitter-zig")]
+            Lang::Zig => &[
+                "@import(",
+                "const std = ",
+                "pub fn ",
+                "comptime ",
+                "!void",
+                "try ",
+                "defer ",
+            ],
+            #[cfg(feature = "dynamic")]
+            Lang::Dynamic(_) => &[],
+        }
+    }
+}
+
+/// Cut `source` to at most [`MAX_BYTES`], at a line break if possible.
+fn truncate(source: &[u8]) -> &[u8] {
+    if source.len() <= MAX_BYTES {
+        return source;
+    }
+
+    let source = &source[..MAX_BYTES];
+
+    source
+        .iter()
+        .rposition(|c| *c == b'\n')
+        .map_or(source, |position| &source[..=position])
+}
+
+/// Number of bytes covered by `ERROR` nodes plus one for each node inserted by error recovery.
+fn error_bytes(node: Node) -> usize {
+    if node.is_error() {
+        return node.byte_range().len().max(1);
+    }
+
+    if node.is_missing() {
+        return 1;
+    }
+
+    if !node.has_error() {
+        return 0;
+    }
+
+    let mut cursor = node.walk();
+    let children = node.children(&mut cursor).collect::<Vec<_>>();
+    children.into_iter().map(error_bytes).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prose() {
+        assert_eq!(Lang::classify(b""), None);
+        assert_eq!(Lang::classify(b"hello world"), None);
+        assert_eq!(
+            Lang::classify(b"The quick brown fox jumps over the lazy dog.\nIt was a sunny day.\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn snippets() {
+        let snippets: &[(Lang, &str)] = &[
+            #[cfg(feature = "tree-sitter-c")]
+            (
+                Lang::C,
+                "#include <stdio.h>\n\ntypedef struct { int x; } point;\n\nint main(void) {\n    point *p = NULL;\n    printf(\"%zu\\n\", sizeof(p->x));\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-cpp")]
+            (
+                Lang::Cpp,
+                "#include <iostream>\n\nnamespace app {\ntemplate <typename T>\nvoid show(T x) {\n    std::cout << x;\n}\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-c-sharp")]
+            (
+                Lang::CSharp,
+                "using System;\n\nnamespace App\n{\n    public class Program\n    {\n        static void Main()\n        {\n            var x = 1;\n            Console.WriteLine(x);\n        }\n    }\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-css")]
+            (
+                Lang::Css,
+                "body {\n  color: red;\n  margin: 0;\n  padding: 4px;\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-dockerfile")]
+            (
+                Lang::Docker,
+                "FROM rust:1\nWORKDIR /app\nCOPY . .\nRUN cargo build --release\nCMD [\"app\"]\n",
+            ),
+            #[cfg(feature = "tree-sitter-go")]
+            (
+                Lang::Go,
+                "package main\n\nimport (\n\t\"fmt\"\n)\n\nfunc main() {\n\tx := 1\n\tfmt.Println(x)\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-haskell")]
+            (
+                Lang::Haskell,
+                "module Main where\n\nimport qualified Data.Map as M\n\nmain :: IO ()\nmain = print (M.size M.empty)\n",
+            ),
+            #[cfg(feature = "tree-sitter-java")]
+            (
+                Lang::Java,
+                "public class Main {\n    public static void main(String[] args) {\n        System.out.println(\"hi\");\n    }\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-javascript")]
+            (
+                Lang::Js,
+                "const fs = require(\"fs\");\n\nfunction main() {\n  console.log(fs);\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-json")]
+            (
+                Lang::Json,
+                "{\n  \"name\": \"app\",\n  \"tags\": [\"a\", \"b\"],\n  \"private\": true\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-kotlin")]
+            (
+                Lang::Kotlin,
+                "data class Point(val x: Int)\n\nfun main() {\n    val p = Point(1)\n    println(p)\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-latex")]
+            (
+                Lang::Latex,
+                "\\documentclass{article}\n\\usepackage{amsmath}\n\\begin{document}\n\\section{Intro}\n\\textbf{Hello}\n\\end{document}\n",
+            ),
+            #[cfg(feature = "tree-sitter-lua")]
+            (
+                Lang::Lua,
+                "local function greet(name)\n  if name ~= nil then\n    return \"hi \" .. name\n  end\nend\n",
+            ),
+            #[cfg(feature = "tree-sitter-md")]
+            (
+                Lang::Markdown,
+                "# Title\n\n## Section\n\n* one\n* [two](https://example.com)\n\n**bold**\n",
+            ),
+            #[cfg(feature = "tree-sitter-nix")]
+            (
+                Lang::Nix,
+                "{ pkgs ? import <nixpkgs> { } }:\n\npkgs.stdenv.mkDerivation {\n  name = \"app\";\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-ocaml")]
+            (
+                Lang::Ocaml,
+                "let rec fact n =\n  match n with\n  | 0 -> 1\n  | n -> n * fact (n - 1)\n\nlet () = print_int (fact 5)\n",
+            ),
+            #[cfg(feature = "tree-sitter-python")]
+            (
+                Lang::Python,
+                "import os\n\nclass App:\n    def __init__(self):\n        self.path = None\n        print(os.getcwd())\n",
+            ),
+            #[cfg(feature = "tree-sitter-rust")]
+            (
+                Lang::Rust,
+                "fn main() {\n    let mut x = 1;\n    x += 1;\n    println!(\"{x}\");\n}\n",
+            ),
+            #[cfg(feature = "tree-sitter-typescript")]
+            (
+                Lang::Ts,
+                "interface Person {\n  readonly name: string;\n  age: number;\n}\n\nexport type People = Person[];\n",
+            ),
+            #[cfg(feature = "tree-sitter-zig")]
+            (
+                Lang::Zig,
+                "const std = @import(\"std\");\n\npub fn main() !void {\n    try std.io.getStdOut().writer().print(\"hi\\n\", .{});\n}\n",
+            ),
+        ];
+
+        for (lang, source) in snippets {
+            let guess = Lang::classify(source.as_bytes()).map(|guess| guess.lang);
+            assert_eq!(guess.as_ref(), Some(lang), "{source}");
+        }
+    }
+}
@@ -0,0 +1,161 @@
+/// A color with 8-bit red, green and blue channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// Parse a color in `#rrggbb` or `#rgb` notation.
+    pub fn from_hex(color: &str) -> Option<Self> {
+        let hex = color.strip_prefix('#')?;
+
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+
+        match hex.len() {
+            6 => Some(Self::new(channel(0..2)?, channel(2..4)?, channel(4..6)?)),
+            3 => Some(Self::new(
+                channel(0..1)? * 17,
+                channel(1..2)? * 17,
+                channel(2..3)? * 17,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Format as `#rrggbb`.
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Hue in degrees, saturation and lightness between 0 and 1.
+    pub fn to_hsl(self) -> (f64, f64, f64) {
+        let (r, g, b) = (
+            self.r as f64 / 255.0,
+            self.g as f64 / 255.0,
+            self.b as f64 / 255.0,
+        );
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        let delta = max - min;
+
+        if delta == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+
+        let hue = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (hue, saturation, lightness)
+    }
+
+    /// Color of `hue` in degrees, `saturation` and `lightness` between 0 and 1.
+    pub fn from_hsl(hue: f64, saturation: f64, lightness: f64) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0).rem_euclid(2.0) - 1.0).abs());
+        let m = lightness - chroma / 2.0;
+
+        let (r, g, b) = match (hue.rem_euclid(360.0) / 60.0) as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        let channel = |c: f64| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Self::new(channel(r), channel(g), channel(b))
+    }
+
+    /// Squared euclidean distance to `other`.
+    pub fn distance(&self, other: &Self) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        d(self.r, other.r) + d(self.g, other.g) + d(self.b, other.b)
+    }
+}
+
+/// `value` without its alpha channel if it is a `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` color.
+/// Like Helix, theme importers treat translucent colors as opaque.
+pub(crate) fn opaque(value: &str) -> Option<String> {
+    let hex = value.strip_prefix('#')?;
+
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let opaque = match hex.len() {
+        4 => &value[..4],
+        8 => &value[..7],
+        _ => value,
+    };
+
+    Rgb::from_hex(opaque).map(|_| opaque.to_string())
+}
+
+/// Hex value of one of the 16 named terminal colors of Helix.
+///
+/// The values are those of the VGA palette, whose normal and bright variants map back to the
+/// intended basic ANSI colors when printed to a terminal with 16 colors.
+pub(crate) fn named(name: &str) -> Option<&'static str> {
+    match name {
+        "black" => Some("#000000"),
+        "red" => Some("#aa0000"),
+        "green" => Some("#00aa00"),
+        "yellow" => Some("#aa5500"),
+        "blue" => Some("#0000aa"),
+        "magenta" => Some("#aa00aa"),
+        "cyan" => Some("#00aaaa"),
+        "gray" => Some("#555555"),
+        "light-red" => Some("#ff5555"),
+        "light-green" => Some("#55ff55"),
+        "light-yellow" => Some("#ffff55"),
+        "light-blue" => Some("#5555ff"),
+        "light-magenta" => Some("#ff55ff"),
+        "light-cyan" => Some("#55ffff"),
+        "light-gray" => Some("#aaaaaa"),
+        "white" => Some("#ffffff"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_alpha() {
+        assert_eq!(Rgb::from_hex("#1e1e1e"), Some(Rgb::new(30, 30, 30)));
+        assert_eq!(Rgb::from_hex("#1e1e1e80"), None);
+        assert_eq!(Rgb::from_hex("#abcd"), None);
+    }
+
+    #[test]
+    fn drop_alpha() {
+        assert_eq!(opaque("#1e1e1e").as_deref(), Some("#1e1e1e"));
+        assert_eq!(opaque("#1e1e1e80").as_deref(), Some("#1e1e1e"));
+        assert_eq!(opaque("#abc").as_deref(), Some("#abc"));
+        assert_eq!(opaque("#abcd").as_deref(), Some("#abc"));
+        assert_eq!(opaque("#1e1e1e8"), None);
+        assert_eq!(opaque("#1e1e1eü"), None);
+        assert_eq!(opaque("red"), None);
+    }
+}
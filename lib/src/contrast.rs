@@ -0,0 +1,218 @@
+use crate::color::Rgb;
+use crate::Theme;
+
+/// Steps of the binary search for the lightness reaching a contrast ratio.
+const SEARCH_STEPS: usize = 24;
+
+/// Contrast of the text color of a scope against its background.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Contrast {
+    /// Scope as defined by the theme.
+    pub scope: String,
+    /// Text color.
+    pub foreground: String,
+    /// Background color, the theme's background unless the style sets its own.
+    pub background: String,
+    /// WCAG 2.x contrast ratio between 1 and 21.
+    pub ratio: f64,
+    /// APCA lightness contrast Lc between about -108 and 106, negative for light text on dark
+    /// backgrounds.
+    pub apca: f64,
+}
+
+impl Contrast {
+    /// Minimum ratio of WCAG 2.x level AA for normal text.
+    pub const AA: f64 = 4.5;
+    /// Minimum ratio of WCAG 2.x level AA for large text.
+    pub const AA_LARGE: f64 = 3.0;
+    /// Minimum ratio of WCAG 2.x level AAA for normal text.
+    pub const AAA: f64 = 7.0;
+
+    /// Whether the contrast ratio is at least `minimum`.
+    pub fn passes(&self, minimum: f64) -> bool {
+        self.ratio >= minimum
+    }
+}
+
+impl Theme {
+    /// Contrast of every scope with a color or background against its background, ordered by
+    /// scope. Reversed styles are measured with swapped colors and colors that are not hex
+    /// values are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Contrast, Theme};
+    ///
+    /// let theme = Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+    ///
+    /// for contrast in theme.contrast().iter().filter(|contrast| !contrast.passes(Contrast::AA)) {
+    ///     println!("{}: {:.2} (Lc {:.1})", contrast.scope, contrast.ratio, contrast.apca);
+    /// }
+    /// ```
+    pub fn contrast(&self) -> Vec<Contrast> {
+        let mut scopes = self
+            .scopes()
+            .filter(|(_, style)| style.color.is_some() || style.background.is_some())
+            .filter_map(|(scope, style)| {
+                let mut foreground = style.color.as_ref().unwrap_or(&self.foreground);
+                let mut background = style.background.as_ref().unwrap_or(&self.background);
+
+                if style.is_reversed {
+                    std::mem::swap(&mut foreground, &mut background);
+                }
+
+                let fg = Rgb::from_hex(foreground)?;
+                let bg = Rgb::from_hex(background)?;
+
+                Some(Contrast {
+                    scope: scope.to_string(),
+                    foreground: foreground.clone(),
+                    background: background.clone(),
+                    ratio: ratio(fg, bg),
+                    apca: apca(fg, bg),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        scopes.sort_by(|a, b| a.scope.cmp(&b.scope));
+        scopes
+    }
+
+    /// Change the lightness of all text colors with a contrast ratio below `minimum`, keeping
+    /// their hue and saturation, and return the adjusted scopes.
+    ///
+    /// Colors are lightened on dark and darkened on light backgrounds just enough to reach
+    /// `minimum`, or as far as possible if it cannot be reached. Reversed styles are not changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Contrast, Style, Theme};
+    ///
+    /// let mut theme = Theme::builder()
+    ///     .background("#1e1e2e")
+    ///     .style("comment", Style { color: Some("#45475a".into()), ..Default::default() })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(theme.adjust_contrast(Contrast::AA), vec!["comment".to_string()]);
+    /// assert!(theme.contrast().iter().all(|contrast| contrast.passes(Contrast::AA)));
+    /// ```
+    pub fn adjust_contrast(&mut self, minimum: f64) -> Vec<String> {
+        let mut adjusted = Vec::new();
+
+        for (scope, style) in self.styles.iter_mut() {
+            if style.is_reversed || (style.color.is_none() && style.background.is_none()) {
+                continue;
+            }
+
+            let foreground = style.color.as_ref().unwrap_or(&self.foreground);
+            let background = style.background.as_ref().unwrap_or(&self.background);
+
+            let (Some(fg), Some(bg)) = (Rgb::from_hex(foreground), Rgb::from_hex(background))
+            else {
+                continue;
+            };
+
+            if ratio(fg, bg) >= minimum {
+                continue;
+            }
+
+            style.color = Some(with_contrast(fg, bg, minimum).to_hex());
+            adjusted.push(scope.clone());
+        }
+
+        adjusted.sort();
+        adjusted
+    }
+}
+
+/// Color with the hue and saturation of `fg` and the lightness closest to that of `fg` reaching
+/// a contrast ratio of `minimum` against `bg`.
+fn with_contrast(fg: Rgb, bg: Rgb, minimum: f64) -> Rgb {
+    let (hue, saturation, lightness) = fg.to_hsl();
+    let lighten = ratio(Rgb::new(255, 255, 255), bg) >= ratio(Rgb::new(0, 0, 0), bg);
+
+    // Lightness not reaching and reaching the minimum ratio.
+    let (mut low, mut high) = if lighten {
+        (lightness, 1.0)
+    } else {
+        (lightness, 0.0)
+    };
+
+    for _ in 0..SEARCH_STEPS {
+        let middle = (low + high) / 2.0;
+
+        if ratio(Rgb::from_hsl(hue, saturation, middle), bg) >= minimum {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    Rgb::from_hsl(hue, saturation, high)
+}
+
+/// Relative luminance as defined by WCAG 2.x.
+fn luminance(color: Rgb) -> f64 {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+
+    0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+}
+
+/// WCAG 2.x contrast ratio of `fg` against `bg`.
+fn ratio(fg: Rgb, bg: Rgb) -> f64 {
+    let (a, b) = (luminance(fg), luminance(bg));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// APCA lightness contrast of text `fg` on `bg` following version 0.0.98G-4g of the algorithm.
+fn apca(fg: Rgb, bg: Rgb) -> f64 {
+    let y = |color: Rgb| {
+        let linear = |c: u8| (c as f64 / 255.0).powf(2.4);
+        let y =
+            0.2126729 * linear(color.r) + 0.7151522 * linear(color.g) + 0.072175 * linear(color.b);
+
+        // Soft clamp of near black colors.
+        if y < 0.022 {
+            y + (0.022 - y).powf(1.414)
+        } else {
+            y
+        }
+    };
+
+    let (text, background) = (y(fg), y(bg));
+
+    if (background - text).abs() < 0.0005 {
+        return 0.0;
+    }
+
+    let contrast = if background > text {
+        let contrast = (background.powf(0.56) - text.powf(0.57)) * 1.14;
+
+        if contrast < 0.1 {
+            0.0
+        } else {
+            contrast - 0.027
+        }
+    } else {
+        let contrast = (background.powf(0.65) - text.powf(0.62)) * 1.14;
+
+        if contrast > -0.1 {
+            0.0
+        } else {
+            contrast + 0.027
+        }
+    };
+
+    contrast * 100.0
+}
@@ -0,0 +1,314 @@
+use crate::Lang;
+use std::path::Path;
+
+/// Number of leading lines searched for editor modelines, the same as Vim's default.
+const MODELINE_LINES: usize = 5;
+
+/// Source of a language guess, ordered from the most to the least reliable one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Evidence {
+    /// An Emacs `-*- mode: rust -*-` or Vim `vim: ft=lua` modeline.
+    Modeline,
+    /// A well-known file name such as `Dockerfile`.
+    Filename,
+    /// The interpreter of a `#!` line.
+    Shebang,
+    /// The file extension.
+    Extension,
+}
+
+/// A language guessed by [`Lang::detect()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Guess {
+    /// Guessed language.
+    pub lang: Lang,
+    /// What the guess is based on.
+    pub evidence: Evidence,
+}
+
+impl Lang {
+    /// Guess the language of the file at `path` from its name and extension as well as from the
+    /// shebang and editor modelines in its leading `content`, if given.
+    ///
+    /// Guesses are ranked by [`Evidence`], the most reliable one first, and each language is
+    /// listed at most once. The result is empty if nothing matches a supported language.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Evidence, Lang};
+    ///
+    /// let guesses = Lang::detect("run", Some(b"#!/usr/bin/env python3\nprint(1)\n"));
+    /// assert_eq!(guesses[0].lang, Lang::Python);
+    /// assert_eq!(guesses[0].evidence, Evidence::Shebang);
+    ///
+    /// let guesses = Lang::detect("build.txt", Some(b"// -*- mode: rust -*-\n"));
+    /// assert_eq!(guesses[0].lang, Lang::Rust);
+    ///
+    /// let guesses = Lang::detect("x", Some(b"# vim600: set ft=rust:\n"));
+    /// assert_eq!(guesses[0].lang, Lang::Rust);
+    ///
+    /// let guesses = Lang::detect("x.py", Some(b"# index: ft=rust\n"));
+    /// assert_eq!(guesses[0].lang, Lang::Python);
+    /// assert_eq!(guesses[0].evidence, Evidence::Extension);
+    /// ```
+    pub fn detect<P: AsRef<Path>>(path: P, content: Option<&[u8]>) -> Vec<Guess> {
+        let path = path.as_ref();
+        let mut guesses = Vec::new();
+
+        let mut push = |lang: Option<Lang>, evidence: Evidence| {
+            if let Some(lang) = lang {
+                guesses.push(Guess { lang, evidence });
+            }
+        };
+
+        if let Some(content) = content {
+            let content = String::from_utf8_lossy(content);
+
+            for line in content.lines().take(MODELINE_LINES) {
+                push(modeline(line).and_then(Lang::from_name), Evidence::Modeline);
+            }
+
+            push(
+                content
+                    .lines()
+                    .next()
+                    .and_then(shebang)
+                    .and_then(from_interpreter),
+                Evidence::Shebang,
+            );
+        }
+
+        if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+            push(from_filename(name), Evidence::Filename);
+        }
+
+        push(Lang::from(path), Evidence::Extension);
+
+        guesses.sort_by_key(|guess| guess.evidence);
+
+        let mut unique: Vec<Guess> = Vec::with_capacity(guesses.len());
+
+        for guess in guesses {
+            if !unique.iter().any(|other| other.lang == guess.lang) {
+                unique.push(guess);
+            }
+        }
+
+        unique
+    }
+}
+
+/// Language of well-known file names without a meaningful extension. Build files like `Makefile`
+/// or `CMakeLists.txt` have no bundled grammar and are not recognized.
+fn from_filename(name: &str) -> Option<Lang> {
+    let lower = name.to_ascii_lowercase();
+
+    match lower.as_str() {
+        #[cfg(feature = "tree-sitter-dockerfile")]
+        "dockerfile" | "containerfile" => Some(Lang::Docker),
+        #[cfg(feature = "tree-sitter-dockerfile")]
+        _ if lower.starts_with("dockerfile.") || lower.starts_with("containerfile.") => {
+            Some(Lang::Docker)
+        }
+        #[cfg(feature = "tree-sitter-javascript")]
+        "jakefile" => Some(Lang::Js),
+        #[cfg(feature = "tree-sitter-json")]
+        "flake.lock" | ".babelrc" | ".eslintrc" | ".prettierrc" | "composer.lock" => {
+            Some(Lang::Json)
+        }
+        #[cfg(feature = "tree-sitter-lua")]
+        ".luacheckrc" | ".luacov" => Some(Lang::Lua),
+        #[cfg(feature = "tree-sitter-python")]
+        "sconstruct" | "sconscript" | "wscript" | ".pythonrc" => Some(Lang::Python),
+        _ => None,
+    }
+}
+
+/// Interpreter named by a `#!` line, e.g. `python3` for `#!/usr/bin/env -S python3 -u`.
+fn shebang(line: &str) -> Option<&str> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+
+    Some(program)
+}
+
+/// Language run by `interpreter`, ignoring version suffixes like in `python3.11`.
+fn from_interpreter(interpreter: &str) -> Option<Lang> {
+    let name = interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+
+    match name {
+        #[cfg(feature = "tree-sitter-haskell")]
+        "runghc" | "runhaskell" | "stack" => Some(Lang::Haskell),
+        #[cfg(feature = "tree-sitter-javascript")]
+        "node" | "nodejs" | "qjs" => Some(Lang::Js),
+        #[cfg(feature = "tree-sitter-kotlin")]
+        "kotlin" | "kscript" => Some(Lang::Kotlin),
+        #[cfg(feature = "tree-sitter-lua")]
+        "lua" | "luajit" => Some(Lang::Lua),
+        #[cfg(feature = "tree-sitter-ocaml")]
+        "ocaml" | "ocamlrun" => Some(Lang::Ocaml),
+        #[cfg(feature = "tree-sitter-python")]
+        "python" | "pypy" => Some(Lang::Python),
+        #[cfg(feature = "tree-sitter-rust")]
+        "rust-script" | "run-cargo-script" => Some(Lang::Rust),
+        #[cfg(feature = "tree-sitter-typescript")]
+        "ts-node" | "deno" | "bun" => Some(Lang::Ts),
+        _ => Lang::from_name(name),
+    }
+}
+
+/// Mode or file type set by an Emacs `-*- mode: rust -*-` or Vim `vim: set ft=lua:` modeline.
+fn modeline(line: &str) -> Option<&str> {
+    if let Some((_, rest)) = line.split_once("-*-") {
+        let (variables, _) = rest.split_once("-*-")?;
+        let variables = variables.trim();
+
+        // Either a plain mode name or a list of `variable: value` pairs.
+        if !variables.contains(':') {
+            return Some(variables);
+        }
+
+        return variables.split(';').find_map(|pair| {
+            let (key, value) = pair.split_once(':')?;
+            key.trim()
+                .eq_ignore_ascii_case("mode")
+                .then(|| value.trim())
+        });
+    }
+
+    // Like in Vim, the marker starts the line or follows whitespace.
+    let start = line
+        .char_indices()
+        .filter(|(index, _)| *index == 0 || line[..*index].ends_with(char::is_whitespace))
+        .find_map(|(index, _)| vim_marker(&line[index..]).map(|length| index + length))?;
+
+    line[start..]
+        .split(|c: char| c == ':' || c.is_whitespace())
+        .find_map(|option| {
+            option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+                .or_else(|| option.strip_prefix("syntax="))
+        })
+}
+
+/// Length of the Vim modeline marker starting `text`, i.e. `vi:`, `vim:`, `ex:` or a
+/// version-specific marker such as `vim600:` or `vim>700:`.
+fn vim_marker(text: &str) -> Option<usize> {
+    if let Some(marker) = ["vi:", "vim:", "ex:"]
+        .iter()
+        .find(|marker| text.starts_with(*marker))
+    {
+        return Some(marker.len());
+    }
+
+    let rest = text.strip_prefix("vim")?;
+    let rest = rest.strip_prefix(['<', '=', '>']).unwrap_or(rest);
+    let version = rest.trim_start_matches(|c: char| c.is_ascii_digit());
+
+    if version.len() == rest.len() || !version.starts_with(':') {
+        return None;
+    }
+
+    Some(text.len() - version.len() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vim_modelines() {
+        assert_eq!(modeline("vim: ft=rust"), Some("rust"));
+        assert_eq!(modeline("# vi: set ft=lua:"), Some("lua"));
+        assert_eq!(modeline("//\tex: filetype=c"), Some("c"));
+        assert_eq!(modeline("/* vim: set syntax=python: */"), Some("python"));
+        assert_eq!(modeline("# vim>700: ft=go"), Some("go"));
+    }
+
+    #[test]
+    fn vim_markers_require_leading_whitespace() {
+        assert_eq!(modeline("#vim: ft=rust"), None);
+        assert_eq!(modeline("davi: ft=lua"), None);
+        assert_eq!(modeline("index: ft=c"), None);
+        assert_eq!(modeline("# vimrc: ft=c"), None);
+    }
+
+    #[test]
+    fn emacs_modelines() {
+        assert_eq!(modeline("// -*- rust -*-"), Some("rust"));
+        assert_eq!(modeline("# -*- mode: python -*-"), Some("python"));
+        assert_eq!(
+            modeline(";; -*- coding: utf-8; Mode: lua; -*-"),
+            Some("lua")
+        );
+        assert_eq!(modeline("# -*- coding: utf-8 -*-"), None);
+    }
+
+    #[test]
+    fn shebangs() {
+        assert_eq!(shebang("#!/usr/bin/env python3"), Some("python3"));
+        assert_eq!(shebang("#!/usr/bin/env -S python3 -u"), Some("python3"));
+        assert_eq!(shebang("#!/usr/local/bin/lua"), Some("lua"));
+        assert_eq!(shebang("#!"), None);
+        assert_eq!(shebang("# python3"), None);
+    }
+
+    #[cfg(feature = "tree-sitter-python")]
+    #[test]
+    fn detect_shebang() {
+        for content in [
+            &b"#!/usr/bin/env python3\n"[..],
+            b"#!/usr/bin/env -S python3 -u\n",
+        ] {
+            let guesses = Lang::detect("run", Some(content));
+            assert_eq!(
+                guesses,
+                vec![Guess {
+                    lang: Lang::Python,
+                    evidence: Evidence::Shebang
+                }]
+            );
+        }
+    }
+
+    #[cfg(all(feature = "tree-sitter-rust", feature = "tree-sitter-python"))]
+    #[test]
+    fn detect_ignores_modelines_after_line_five() {
+        let content = b"\n\n\n\n\n# vim: ft=rust\n";
+        assert_eq!(Lang::detect("x.py", Some(content))[0].lang, Lang::Python);
+
+        let content = b"\n\n\n\n# vim: ft=rust\n";
+        assert_eq!(Lang::detect("x.py", Some(content))[0].lang, Lang::Rust);
+    }
+
+    #[cfg(feature = "tree-sitter-dockerfile")]
+    #[test]
+    fn detect_dockerfile() {
+        for name in [
+            "Dockerfile",
+            "containerfile",
+            "Dockerfile.dev",
+            "path/to/Dockerfile",
+        ] {
+            assert_eq!(
+                Lang::detect(name, None),
+                vec![Guess {
+                    lang: Lang::Docker,
+                    evidence: Evidence::Filename
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn detect_makefile() {
+        assert_eq!(from_filename("Makefile"), None);
+        assert!(Lang::detect("Makefile", None).is_empty());
+    }
+}
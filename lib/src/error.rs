@@ -7,10 +7,49 @@ pub enum Error {
     /// TOML data is not structured like a valid Helix theme.
     #[error("toml does not contain valid helix theme")]
     InvalidTheme,
-    /// A color is referenced but is not defined.
-    #[error("toml color {0} not found")]
-    InvalidColorReference(String),
+    /// A color is neither a hex color, a palette entry nor a named terminal color.
+    #[error("invalid color {color} of {key}")]
+    InvalidColor {
+        /// Dotted path of the theme key, e.g. `keyword.fg`.
+        key: String,
+        /// The offending color value or the TOML type of a value that is not a string.
+        color: String,
+    },
+    /// An inherited theme is neither known to the resolver nor bundled.
+    #[error("inherited theme {0} not found")]
+    UnknownTheme(String),
+    /// Themes inherit from each other.
+    #[error("theme {0} inherits from itself")]
+    InheritanceCycle(String),
     /// Failed to highlight source.
     #[error("highlighting failed: {0}")]
     Highlighting(#[from] tree_sitter_highlight::Error),
+    /// VS Code theme could not be parsed as valid JSON.
+    #[cfg(feature = "vscode")]
+    #[error("json parse error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// TextMate theme could not be parsed as valid property list.
+    #[cfg(feature = "tmtheme")]
+    #[error("plist parse error: {0}")]
+    Plist(#[from] plist::Error),
+    /// Base16 scheme could not be parsed as valid YAML.
+    #[cfg(feature = "base16")]
+    #[error("yaml parse error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    /// Failed to read a file.
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A tree-sitter query could not be parsed.
+    #[error("invalid query: {0}")]
+    Query(#[from] tree_sitter::QueryError),
+    /// A query uses a predicate that cannot be evaluated when highlighting.
+    #[error("unsupported query predicate #{0}")]
+    UnsupportedPredicate(String),
+    /// A grammar was generated by an incompatible tree-sitter version.
+    #[error("grammar {0} has incompatible language version {1}")]
+    IncompatibleGrammar(String, usize),
+    /// Failed to load a grammar from a shared library.
+    #[cfg(feature = "dynamic")]
+    #[error("loading grammar library failed: {0}")]
+    Library(#[from] libloading::Error),
 }
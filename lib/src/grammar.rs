@@ -0,0 +1,131 @@
+use crate::queries::Queries;
+use crate::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Arc;
+use tree_sitter::Language;
+use tree_sitter_highlight::HighlightConfiguration;
+
+struct Inner {
+    name: String,
+    language: Language,
+    highlights_query: String,
+    injections_query: String,
+    locals_query: String,
+    // Must be kept alive as long as `language` is used.
+    _library: libloading::Library,
+}
+
+/// A tree-sitter grammar loaded at runtime from a shared library.
+///
+/// Wrap it in [`Lang::Dynamic`](crate::Lang::Dynamic) to render source code with it and register
+/// it with [`Renderer::register()`](crate::Renderer::register) to use it for injected languages.
+/// Grammars compare equal only to clones of themselves, loading the same library twice yields
+/// distinct grammars.
+#[derive(Clone)]
+pub struct Grammar {
+    inner: Arc<Inner>,
+}
+
+impl Grammar {
+    /// Load the grammar `name` from the shared library at `library` and its `highlights.scm`,
+    /// `injections.scm` and `locals.scm` queries from the `queries` directory. Missing query files
+    /// are treated as empty. Helix `; inherits` directives are resolved against the sibling
+    /// directories of `queries`, so `queries` may point into a Helix runtime, e.g.
+    /// `runtime/queries/rust`.
+    ///
+    /// The library must export a `tree_sitter_<name>` function, dashes in `name` are replaced by
+    /// underscores, e.g. `tree_sitter_c_sharp` for `c-sharp`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`Error`] if the library or the symbol cannot be loaded, the grammar was
+    /// generated by an incompatible tree-sitter version or the queries cannot be read or are
+    /// invalid.
+    ///
+    /// # Safety
+    ///
+    /// Loading a library runs its initialization routines and the `tree_sitter_<name>` symbol is
+    /// assumed to be a tree-sitter language function. Both are only sound for genuine tree-sitter
+    /// grammars.
+    pub unsafe fn load<P: AsRef<Path>, Q: AsRef<Path>>(
+        name: &str,
+        library: P,
+        queries: Q,
+    ) -> Result<Self, Error> {
+        let library = libloading::Library::new(library.as_ref())?;
+        let symbol = format!("tree_sitter_{}", name.replace('-', "_"));
+        let language = library.get::<unsafe extern "C" fn() -> Language>(symbol.as_bytes())?();
+
+        if !(tree_sitter::MIN_COMPATIBLE_LANGUAGE_VERSION..=tree_sitter::LANGUAGE_VERSION)
+            .contains(&language.version())
+        {
+            return Err(Error::IncompatibleGrammar(
+                name.to_string(),
+                language.version(),
+            ));
+        }
+
+        let queries = Queries::from_dir(queries.as_ref())?;
+
+        let grammar = Self {
+            inner: Arc::new(Inner {
+                name: name.to_string(),
+                language,
+                highlights_query: queries.highlights,
+                injections_query: queries.injections,
+                locals_query: queries.locals,
+                _library: library,
+            }),
+        };
+
+        grammar.try_config()?;
+        Ok(grammar)
+    }
+
+    /// Name of the grammar.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    pub(crate) fn language(&self) -> Language {
+        self.inner.language
+    }
+
+    fn try_config(&self) -> Result<HighlightConfiguration, tree_sitter::QueryError> {
+        HighlightConfiguration::new(
+            self.inner.language,
+            &self.inner.highlights_query,
+            &self.inner.injections_query,
+            &self.inner.locals_query,
+        )
+    }
+
+    pub(crate) fn config(&self) -> HighlightConfiguration {
+        // Queries have been validated when loading the grammar.
+        self.try_config().expect("loading dynamic grammar")
+    }
+}
+
+impl PartialEq for Grammar {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for Grammar {}
+
+impl Hash for Grammar {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.inner).hash(state);
+    }
+}
+
+impl fmt::Debug for Grammar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Grammar")
+            .field("name", &self.inner.name)
+            .finish()
+    }
+}
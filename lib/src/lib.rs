@@ -21,6 +21,10 @@
 //! let rust_lang = tree_painter::Lang::from("file.rs").unwrap();
 //! ```
 //!
+//! For extensionless files such as `Dockerfile`s or scripts, [`Lang::detect()`] also considers
+//! well-known file names, shebang lines and Emacs or Vim modelines and ranks its guesses.
+//! Without any file name, [`Lang::classify()`] guesses the language from the source code itself.
+//!
 //! Then load a Helix theme:
 //!
 //! ```no_run
@@ -30,6 +34,9 @@
 //! let theme = tree_painter::Theme::from_helix(&tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
 //! ```
 //!
+//! Themes declaring `inherits = "name"` are based on the bundled theme of that name or, with
+//! [`Theme::from_helix_with()`], on any theme you resolve yourself.
+//!
 //! Finally render the code:
 //!
 //! ```no_run
@@ -46,6 +53,13 @@
 //!
 //! Note that each line is formatted using `<span>`s and CSS classes. In order to map the CSS
 //! classes to the theme's color include the output of [`Renderer::css()`] appropriately.
+//! With [`RendererBuilder::dark_theme()`] the stylesheet switches between a light and a dark theme
+//! depending on the user's preferred color scheme.
+//! [`Renderer::render_fragment()`] and [`Renderer::render_document()`] wrap the lines in a
+//! `<pre><code>` block or a complete HTML document with the theme's colors applied.
+//!
+//! To print highlighted code to a terminal use [`Renderer::render_ansi()`] instead, which colors
+//! the code with ANSI escape sequences.
 //!
 //! # Feature flags
 //!
@@ -57,17 +71,54 @@
 //! [dependencies]
 //! tree-painter = { version = "0", default-features = false, features = ["tree-sitter-c"] }
 //! ```
+//!
+//! The `dynamic` feature flag enables loading additional grammars at runtime from shared
+//! libraries with `Grammar::load()`.
+//!
+//! The `vscode` and `tmtheme` feature flags enable importing VS Code color themes with
+//! `Theme::from_vscode()` and TextMate or Sublime Text themes with `Theme::from_tmtheme()`.
+//! The `base16` feature flag enables generating themes from base16 and base24 schemes with
+//! `Theme::from_base16()`.
 
 use std::path::Path;
+use tree_sitter::Language;
 use tree_sitter_highlight::HighlightConfiguration;
 
+mod ansi;
+#[cfg(feature = "base16")]
+mod base16;
+mod classes;
+mod classify;
+mod color;
+mod contrast;
+mod detect;
 mod error;
+#[cfg(feature = "dynamic")]
+mod grammar;
+mod queries;
 mod renderer;
+#[cfg(any(feature = "vscode", feature = "tmtheme"))]
+mod textmate;
 mod theme;
+#[cfg(feature = "tmtheme")]
+mod tmtheme;
+mod token;
+#[cfg(feature = "vscode")]
+mod vscode;
 
+pub use ansi::ColorDepth;
+pub use classes::ClassNames;
+pub use classify::Classification;
+pub use contrast::Contrast;
+pub use detect::{Evidence, Guess};
 pub use error::Error;
-pub use renderer::Renderer;
-pub use theme::Theme;
+#[cfg(feature = "dynamic")]
+pub use grammar::Grammar;
+pub use renderer::{Renderer, RendererBuilder, HIGHLIGHT_NAMES};
+#[cfg(any(feature = "vscode", feature = "tmtheme"))]
+pub use textmate::TEXTMATE_SCOPES;
+pub use theme::{Style, Theme, ThemeBuilder, UnderlineStyle};
+pub use token::Token;
 
 #[cfg(feature = "themes")]
 /// Bundled themes for use with [`Theme::from_helix()`].
@@ -149,6 +200,9 @@ pub enum Lang {
     Ts,
     #[cfg(feature = "tree-sitter-zig")]
     Zig,
+    /// Grammar loaded at runtime from a shared library.
+    #[cfg(feature = "dynamic")]
+    Dynamic(Grammar),
 }
 
 impl Lang {
@@ -167,7 +221,7 @@ impl Lang {
         path.as_ref()
             .extension()
             .and_then(|e| e.to_str())
-            .and_then(|e| Self::from_extension(e))
+            .and_then(Self::from_extension)
     }
 
     /// Guesses a language from a plain file extension.
@@ -191,7 +245,7 @@ impl Lang {
             #[cfg(feature = "tree-sitter-cpp")]
             "cpp" | "cc" | "cxx" => Some(Lang::Cpp),
             #[cfg(feature = "tree-sitter-dockerfile")]
-            "docker" => Some(Lang::Docker),
+            "dockerfile" => Some(Lang::Docker),
             // #[cfg(feature = "tree-sitter-clojure")]
             // "clj" | "cljs" | "cljc" => Some(Lang::Clojure),
             #[cfg(feature = "tree-sitter-go")]
@@ -201,7 +255,7 @@ impl Lang {
             // #[cfg(feature = "tree-sitter-html")]
             // "html" => Some(Lang::Html),
             #[cfg(feature = "tree-sitter-java")]
-            "java" => Some(Lang::Js),
+            "java" => Some(Lang::Java),
             #[cfg(feature = "tree-sitter-javascript")]
             "js" => Some(Lang::Js),
             #[cfg(feature = "tree-sitter-json")]
@@ -242,6 +296,190 @@ impl Lang {
         }
     }
 
+    /// Resolves a language name as used by tree-sitter injection queries and Markdown code
+    /// fences, e.g. `rust`, `javascript` or `c++`.
+    ///
+    /// The name is matched case-insensitively against file extensions, common aliases and the
+    /// [`INFOS`] identifiers and names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lang = tree_painter::Lang::from_name("JavaScript");
+    /// assert_eq!(lang, Some(tree_painter::Lang::Js));
+    ///
+    /// let lang = tree_painter::Lang::from_name("brainfuck");
+    /// assert_eq!(lang, None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_lowercase();
+
+        Self::from_extension(&name)
+            .or_else(|| Self::from_alias(&name))
+            .or_else(|| {
+                INFOS
+                    .iter()
+                    .find(|info| info.id == name || info.name.eq_ignore_ascii_case(&name))
+                    .and_then(|info| {
+                        Self::from_extension(info.id).or_else(|| Self::from_alias(info.id))
+                    })
+            })
+    }
+
+    /// Maps common lower-case language aliases that are not file extensions.
+    fn from_alias(name: &str) -> Option<Self> {
+        match name {
+            #[cfg(feature = "tree-sitter-c-sharp")]
+            "c#" | "csharp" | "c_sharp" | "c-sharp" => Some(Lang::CSharp),
+            #[cfg(feature = "tree-sitter-cpp")]
+            "c++" | "hpp" | "hh" => Some(Lang::Cpp),
+            #[cfg(feature = "tree-sitter-dockerfile")]
+            "docker" | "containerfile" => Some(Lang::Docker),
+            #[cfg(feature = "tree-sitter-go")]
+            "golang" => Some(Lang::Go),
+            #[cfg(feature = "tree-sitter-haskell")]
+            "haskell" => Some(Lang::Haskell),
+            #[cfg(feature = "tree-sitter-javascript")]
+            "javascript" | "ecmascript" | "jsx" | "mjs" | "cjs" => Some(Lang::Js),
+            #[cfg(feature = "tree-sitter-kotlin")]
+            "kotlin" | "kts" => Some(Lang::Kotlin),
+            #[cfg(feature = "tree-sitter-latex")]
+            "latex" => Some(Lang::Latex),
+            #[cfg(feature = "tree-sitter-md")]
+            "markdown" | "markdown_block" => Some(Lang::Markdown),
+            #[cfg(feature = "tree-sitter-ocaml")]
+            "ocaml" => Some(Lang::Ocaml),
+            #[cfg(feature = "tree-sitter-python")]
+            "python" | "python3" | "py3" => Some(Lang::Python),
+            #[cfg(feature = "tree-sitter-rust")]
+            "rust" => Some(Lang::Rust),
+            #[cfg(feature = "tree-sitter-typescript")]
+            "typescript" => Some(Lang::Ts),
+            _ => None,
+        }
+    }
+
+    /// Name of the language in Helix, i.e. its directory in the Helix runtime queries.
+    fn helix_name(&self) -> &str {
+        match self {
+            #[cfg(feature = "tree-sitter-c")]
+            Lang::C => "c",
+            #[cfg(feature = "tree-sitter-cpp")]
+            Lang::Cpp => "cpp",
+            #[cfg(feature = "tree-sitter-c-sharp")]
+            Lang::CSharp => "c-sharp",
+            #[cfg(feature = "tree-sitter-css")]
+            Lang::Css => "css",
+            #[cfg(feature = "tree-sitter-dockerfile")]
+            Lang::Docker => "dockerfile",
+            #[cfg(feature = "tree-sitter-go")]
+            Lang::Go => "go",
+            #[cfg(feature = "tree-sitter-haskell")]
+            Lang::Haskell => "haskell",
+            #[cfg(feature = "tree-sitter-java")]
+            Lang::Java => "java",
+            #[cfg(feature = "tree-sitter-javascript")]
+            Lang::Js => "javascript",
+            #[cfg(feature = "tree-sitter-json")]
+            Lang::Json => "json",
+            #[cfg(feature = "tree-sitter-kotlin")]
+            Lang::Kotlin => "kotlin",
+            #[cfg(feature = "tree-sitter-latex")]
+            Lang::Latex => "latex",
+            #[cfg(feature = "tree-sitter-lua")]
+            Lang::Lua => "lua",
+            #[cfg(feature = "tree-sitter-md")]
+            Lang::Markdown => "markdown",
+            #[cfg(feature = "tree-sitter-nix")]
+            Lang::Nix => "nix",
+            #[cfg(feature = "tree-sitter-ocaml")]
+            Lang::Ocaml => "ocaml",
+            #[cfg(feature = "tree-sitter-python")]
+            Lang::Python => "python",
+            #[cfg(feature = "tree-sitter-rust")]
+            Lang::Rust => "rust",
+            #[cfg(feature = "tree-sitter-typescript")]
+            Lang::Ts => "typescript",
+            #[cfg(feature = "tree-sitter-zig")]
+            Lang::Zig => "zig",
+            #[cfg(feature = "dynamic")]
+            Lang::Dynamic(grammar) => grammar.name(),
+        }
+    }
+
+    /// The tree-sitter language used to parse source code.
+    fn language(&self) -> Language {
+        match self {
+            #[cfg(feature = "tree-sitter-c")]
+            Lang::C => tree_sitter_c::language(),
+            #[cfg(feature = "tree-sitter-cpp")]
+            Lang::Cpp => tree_sitter_cpp::language(),
+            #[cfg(feature = "tree-sitter-c-sharp")]
+            Lang::CSharp => tree_sitter_c_sharp::language(),
+            #[cfg(feature = "tree-sitter-css")]
+            Lang::Css => tree_sitter_css::language(),
+            #[cfg(feature = "tree-sitter-dockerfile")]
+            Lang::Docker => tree_sitter_dockerfile::language(),
+            #[cfg(feature = "tree-sitter-go")]
+            Lang::Go => tree_sitter_go::language(),
+            #[cfg(feature = "tree-sitter-haskell")]
+            Lang::Haskell => tree_sitter_haskell::language(),
+            #[cfg(feature = "tree-sitter-java")]
+            Lang::Java => tree_sitter_java::language(),
+            #[cfg(feature = "tree-sitter-javascript")]
+            Lang::Js => tree_sitter_javascript::language(),
+            #[cfg(feature = "tree-sitter-json")]
+            Lang::Json => tree_sitter_json::language(),
+            #[cfg(feature = "tree-sitter-kotlin")]
+            Lang::Kotlin => tree_sitter_kotlin::language(),
+            #[cfg(feature = "tree-sitter-latex")]
+            Lang::Latex => tree_sitter_latex::language(),
+            #[cfg(feature = "tree-sitter-lua")]
+            Lang::Lua => tree_sitter_lua::language(),
+            #[cfg(feature = "tree-sitter-md")]
+            Lang::Markdown => tree_sitter_md::language(),
+            #[cfg(feature = "tree-sitter-nix")]
+            Lang::Nix => tree_sitter_nix::language(),
+            #[cfg(feature = "tree-sitter-ocaml")]
+            Lang::Ocaml => tree_sitter_ocaml::language_ocaml(),
+            #[cfg(feature = "tree-sitter-python")]
+            Lang::Python => tree_sitter_python::language(),
+            #[cfg(feature = "tree-sitter-rust")]
+            Lang::Rust => tree_sitter_rust::language(),
+            #[cfg(feature = "tree-sitter-typescript")]
+            Lang::Ts => tree_sitter_typescript::language_typescript(),
+            #[cfg(feature = "tree-sitter-zig")]
+            Lang::Zig => tree_sitter_zig::language(),
+            #[cfg(feature = "dynamic")]
+            Lang::Dynamic(grammar) => grammar.language(),
+        }
+    }
+
+    /// Build the highlight configuration from the queries found in the Helix-style runtime query
+    /// directory `runtime` or from the bundled queries if there are none.
+    fn runtime_config(&self, runtime: &Path) -> Result<HighlightConfiguration, Error> {
+        let queries = match queries::Queries::from_helix_runtime(runtime, self.helix_name())? {
+            Some(queries) => queries,
+            None => return Ok(self.config()),
+        };
+
+        let config = HighlightConfiguration::new(
+            self.language(),
+            &queries.highlights,
+            &queries.injections,
+            &queries.locals,
+        )?;
+
+        // tree-sitter-highlight ignores other predicates, so their patterns would always match.
+        for index in 0..config.query.pattern_count() {
+            if let Some(predicate) = config.query.general_predicates(index).first() {
+                return Err(Error::UnsupportedPredicate(predicate.operator.to_string()));
+            }
+        }
+
+        Ok(config)
+    }
+
     fn config(&self) -> HighlightConfiguration {
         match self {
             // #[cfg(feature = "tree-sitter-bash")]
@@ -261,10 +499,13 @@ impl Lang {
             )
             .expect("loading tree-sitter-c"),
             #[cfg(feature = "tree-sitter-c-sharp")]
-            Lang::CSharp => {
-                HighlightConfiguration::new(tree_sitter_c_sharp::language(), "", "", "")
-                    .expect("loading tree-sitter-c-sharp")
-            }
+            Lang::CSharp => HighlightConfiguration::new(
+                tree_sitter_c_sharp::language(),
+                include_str!("queries/c-sharp/highlights.scm"),
+                "",
+                "",
+            )
+            .expect("loading tree-sitter-c-sharp"),
             // #[cfg(feature = "tree-sitter-clojure")]
             // Lang::Clojure => HighlightConfiguration::new(
             //     tree_sitter_clojure::language(),
@@ -290,10 +531,13 @@ impl Lang {
             )
             .expect("loading tree-sitter-css"),
             #[cfg(feature = "tree-sitter-dockerfile")]
-            Lang::Docker => {
-                HighlightConfiguration::new(tree_sitter_dockerfile::language(), "", "", "")
-                    .expect("loading tree-sitter-dockerfile")
-            }
+            Lang::Docker => HighlightConfiguration::new(
+                tree_sitter_dockerfile::language(),
+                include_str!("queries/dockerfile/highlights.scm"),
+                "",
+                "",
+            )
+            .expect("loading tree-sitter-dockerfile"),
             #[cfg(feature = "tree-sitter-go")]
             Lang::Go => HighlightConfiguration::new(
                 tree_sitter_go::language(),
@@ -343,8 +587,13 @@ impl Lang {
             )
             .expect("loading tree-sitter-json"),
             #[cfg(feature = "tree-sitter-kotlin")]
-            Lang::Kotlin => HighlightConfiguration::new(tree_sitter_kotlin::language(), "", "", "")
-                .expect("loading tree-sitter-md"),
+            Lang::Kotlin => HighlightConfiguration::new(
+                tree_sitter_kotlin::language(),
+                include_str!("queries/kotlin/highlights.scm"),
+                "",
+                "",
+            )
+            .expect("loading tree-sitter-kotlin"),
             // #[cfg(feature = "tree-sitter-julia")]
             // Lang::Julia => HighlightConfiguration::new(
             //     tree_sitter_julia::language(),
@@ -354,11 +603,21 @@ impl Lang {
             // )
             // .expect("loading tree-sitter-julia"),
             #[cfg(feature = "tree-sitter-latex")]
-            Lang::Latex => HighlightConfiguration::new(tree_sitter_latex::language(), "", "", "")
-                .expect("loading tree-sitter-json"),
+            Lang::Latex => HighlightConfiguration::new(
+                tree_sitter_latex::language(),
+                include_str!("queries/latex/highlights.scm"),
+                "",
+                "",
+            )
+            .expect("loading tree-sitter-latex"),
             #[cfg(feature = "tree-sitter-lua")]
-            Lang::Lua => HighlightConfiguration::new(tree_sitter_lua::language(), "", "", "")
-                .expect("loading tree-sitter-lua"),
+            Lang::Lua => HighlightConfiguration::new(
+                tree_sitter_lua::language(),
+                include_str!("queries/lua/highlights.scm"),
+                "",
+                include_str!("queries/lua/locals.scm"),
+            )
+            .expect("loading tree-sitter-lua"),
             #[cfg(feature = "tree-sitter-md")]
             Lang::Markdown => HighlightConfiguration::new(
                 tree_sitter_md::language(),
@@ -453,6 +712,8 @@ impl Lang {
                 "",
             )
             .expect("loading tree-sitter-zig"),
+            #[cfg(feature = "dynamic")]
+            Lang::Dynamic(grammar) => grammar.config(),
         }
     }
 }
@@ -472,6 +733,22 @@ impl Info {
 }
 
 /// Language info mappings.
+///
+/// # Examples
+///
+/// Every enabled language is resolved by its id and loads its highlight queries:
+///
+/// ```
+/// use tree_painter::{Lang, Renderer, Theme, INFOS};
+///
+/// let theme = Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+/// let mut renderer = Renderer::new(theme);
+///
+/// for info in INFOS.iter() {
+///     let lang = Lang::from_name(info.id).expect(info.name);
+///     renderer.tokens(&lang, b"x\n").expect(info.name);
+/// }
+/// ```
 pub const INFOS: [Info; 20] = [
     #[cfg(feature = "tree-sitter-c")]
     Info::new("c", "C"),
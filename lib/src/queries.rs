@@ -0,0 +1,276 @@
+use crate::Error;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Highlights, injections and locals queries of a language.
+pub(crate) struct Queries {
+    pub highlights: String,
+    pub injections: String,
+    pub locals: String,
+}
+
+impl Queries {
+    /// Read the queries of the language `name` from a Helix-style runtime query directory `root`,
+    /// i.e. from `root/<name>/highlights.scm` and so on. Returns [`None`] if there is no
+    /// highlights query for the language.
+    pub fn from_helix_runtime(root: &Path, name: &str) -> Result<Option<Self>, Error> {
+        if !root.join(name).join("highlights.scm").is_file() {
+            return Ok(None);
+        }
+
+        Self::from_dir(&root.join(name)).map(Some)
+    }
+
+    /// Read the queries stored in `dir`. Missing query files are treated as empty,
+    /// `; inherits` directives are resolved against sibling directories of `dir` and `#any-of?`
+    /// predicates are rewritten for tree-sitter-highlight.
+    pub fn from_dir(dir: &Path) -> Result<Self, Error> {
+        let root = dir.parent();
+        let mut visited = dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| vec![name.to_string()])
+            .unwrap_or_default();
+
+        let mut read =
+            |file| read(dir, root, file, &mut visited).map(|query| rewrite_any_of(&query));
+
+        Ok(Self {
+            highlights: read("highlights.scm")?,
+            injections: read("injections.scm")?,
+            locals: read("locals.scm")?,
+        })
+    }
+}
+
+/// Languages listed by a Helix `; inherits: ecma,jsx` directive.
+fn inherits(line: &str) -> Option<&str> {
+    let rest = line
+        .trim_start()
+        .strip_prefix(';')?
+        .trim_start_matches(';')
+        .trim_start()
+        .strip_prefix("inherits")?
+        .trim_start();
+
+    Some(rest.strip_prefix(':').unwrap_or(rest).trim())
+}
+
+/// Read query `file` from `dir` and replace `; inherits` directives with the same query of the
+/// inherited languages in `root`. `visited` holds the chain of languages to break cycles.
+fn read(
+    dir: &Path,
+    root: Option<&Path>,
+    file: &str,
+    visited: &mut Vec<String>,
+) -> Result<String, Error> {
+    let query = match std::fs::read_to_string(dir.join(file)) {
+        Ok(query) => query,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(String::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut result = String::with_capacity(query.len());
+
+    for line in query.lines() {
+        match (inherits(line), root) {
+            (Some(languages), Some(root)) => {
+                for language in languages.split(',').map(str::trim) {
+                    if language.is_empty() || visited.iter().any(|name| name == language) {
+                        continue;
+                    }
+
+                    visited.push(language.to_string());
+                    result.push_str(&read(&root.join(language), Some(root), file, visited)?);
+                    result.push('\n');
+                    visited.pop();
+                }
+            }
+            _ => {
+                result.push_str(line);
+                result.push('\n');
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Rewrite the `#any-of?` and `#not-any-of?` predicates of Helix queries, which
+/// tree-sitter-highlight does not evaluate, into equivalent `#match?` and `#not-match?`
+/// predicates, e.g. `(#any-of? @x "a" "b")` into `(#match? @x "^(a|b)$")`.
+fn rewrite_any_of(query: &str) -> String {
+    let mut result = String::with_capacity(query.len());
+    let mut rest = query;
+
+    while let Some(start) = rest.find("(#") {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let rewritten = [("(#any-of?", "match?"), ("(#not-any-of?", "not-match?")]
+            .into_iter()
+            .find_map(|(prefix, operator)| {
+                let body = rest.strip_prefix(prefix)?;
+                let (capture, values, length) = any_of(body)?;
+                Some((operator, capture, values, &body[length..]))
+            });
+
+        match rewritten {
+            Some((operator, capture, values, remainder)) => {
+                let pattern = values
+                    .iter()
+                    .map(|value| regex_escape(value))
+                    .collect::<Vec<_>>()
+                    .join("|");
+
+                let pattern = format!("^({pattern})$")
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"");
+
+                let _ = write!(result, "(#{operator} @{capture} \"{pattern}\")");
+                rest = remainder;
+            }
+            None => {
+                result.push_str("(#");
+                rest = &rest[2..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Parse the capture and string arguments of an `#any-of?` predicate up to and including its
+/// closing parenthesis, returning them and the number of bytes consumed.
+fn any_of(body: &str) -> Option<(&str, Vec<String>, usize)> {
+    let trimmed = body.trim_start();
+    let capture = trimmed.strip_prefix('@')?;
+    let end = capture.find(|c: char| c.is_whitespace() || c == ')')?;
+    let mut rest = &capture[end..];
+    let capture = &capture[..end];
+    let mut values = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+
+        if let Some(remainder) = rest.strip_prefix(')') {
+            return Some((capture, values, body.len() - remainder.len()));
+        }
+
+        let mut chars = rest.strip_prefix('"')?.char_indices();
+        let mut value = String::new();
+
+        let end = loop {
+            match chars.next()? {
+                (index, '"') => break index,
+                (_, '\\') => match chars.next()?.1 {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    c => value.push(c),
+                },
+                (_, c) => value.push(c),
+            }
+        };
+
+        values.push(value);
+        rest = &rest[end + 2..];
+    }
+}
+
+/// Escape the characters of `text` with a special meaning in regular expressions.
+fn regex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Create a fresh runtime query directory containing `highlights.scm` files for the given
+    /// languages.
+    fn runtime(name: &str, languages: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("tree-painter-{name}"));
+        let _ = std::fs::remove_dir_all(&root);
+
+        for (language, highlights) in languages {
+            std::fs::create_dir_all(root.join(language)).unwrap();
+            std::fs::write(root.join(language).join("highlights.scm"), highlights).unwrap();
+        }
+
+        root
+    }
+
+    #[test]
+    fn rewrite_any_of_escapes_metacharacters_and_quotes() {
+        assert_eq!(
+            rewrite_any_of(r#"((identifier) @x (#any-of? @x "a.b" "c\"d" "e|f"))"#),
+            r#"((identifier) @x (#match? @x "^(a\\.b|c\"d|e\\|f)$"))"#
+        );
+        assert_eq!(
+            rewrite_any_of(r#"((identifier) @x (#not-any-of? @x "(" "$"))"#),
+            r#"((identifier) @x (#not-match? @x "^(\\(|\\$)$"))"#
+        );
+    }
+
+    #[test]
+    fn rewrite_any_of_keeps_other_predicates() {
+        let query = r#"((identifier) @x (#match? @x "^[A-Z]") (#eq? @x "Self"))"#;
+        assert_eq!(rewrite_any_of(query), query);
+    }
+
+    #[test]
+    fn nested_inherits() {
+        let root = runtime(
+            "nested-inherits",
+            &[
+                ("a", "; inherits: b\n(a)"),
+                ("b", "; inherits: c\n(b)"),
+                ("c", "(c)"),
+            ],
+        );
+
+        let queries = Queries::from_dir(&root.join("a")).unwrap();
+        assert_eq!(queries.highlights, "(c)\n\n(b)\n\n(a)\n");
+        assert!(queries.injections.is_empty());
+    }
+
+    #[test]
+    fn inherits_cycle() {
+        let root = runtime(
+            "inherits-cycle",
+            &[("a", "; inherits: b\n(a)"), ("b", "; inherits: a\n(b)")],
+        );
+
+        let queries = Queries::from_dir(&root.join("a")).unwrap();
+        assert_eq!(queries.highlights, "(b)\n\n(a)\n");
+    }
+
+    #[cfg(feature = "tree-sitter-rust")]
+    #[test]
+    fn reject_unknown_predicate() {
+        let root = runtime(
+            "unknown-predicate",
+            &[(
+                "rust",
+                "((identifier) @variable (#has-ancestor? @variable function_item))",
+            )],
+        );
+
+        assert!(matches!(
+            crate::Lang::Rust.runtime_config(&root),
+            Err(Error::UnsupportedPredicate(operator)) if operator == "has-ancestor?"
+        ));
+    }
+}
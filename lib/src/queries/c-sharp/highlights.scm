@@ -0,0 +1,154 @@
+; Highlights for tree-sitter-c-sharp using Helix scopes.
+
+(comment) @comment
+
+[
+  (string_literal)
+  (verbatim_string_literal)
+  (interpolated_string_expression)
+] @string
+
+(escape_sequence) @constant.character.escape
+(character_literal) @constant.character
+(integer_literal) @constant.numeric.integer
+(real_literal) @constant.numeric.float
+(boolean_literal) @constant.builtin.boolean
+(null_literal) @constant.builtin
+
+(predefined_type) @type.builtin
+(implicit_type) @type.builtin
+
+(class_declaration name: (identifier) @type)
+(struct_declaration name: (identifier) @type)
+(interface_declaration name: (identifier) @type)
+(enum_declaration name: (identifier) @type)
+(type_parameter (identifier) @type.parameter)
+(object_creation_expression type: (identifier) @type)
+(variable_declaration type: (identifier) @type)
+(parameter type: (identifier) @type)
+
+(namespace_declaration name: (_) @namespace)
+(using_directive (identifier) @namespace)
+(using_directive (qualified_name) @namespace)
+
+(constructor_declaration name: (identifier) @constructor)
+(method_declaration name: (identifier) @function.method)
+(invocation_expression
+  function: (member_access_expression name: (identifier) @function.method))
+(invocation_expression function: (identifier) @function)
+
+(member_access_expression name: (identifier) @variable.other.member)
+(property_declaration name: (identifier) @variable.other.member)
+(parameter name: (identifier) @variable.parameter)
+
+(attribute name: (_) @attribute)
+
+(modifier) @keyword.storage.modifier
+
+(identifier) @variable
+
+[
+  "if"
+  "else"
+  "switch"
+  "case"
+  "default"
+  "when"
+] @keyword.control.conditional
+
+[
+  "for"
+  "foreach"
+  "while"
+  "do"
+  "break"
+  "continue"
+] @keyword.control.repeat
+
+"goto" @keyword.control
+
+[
+  "return"
+  "yield"
+] @keyword.control.return
+
+[
+  "try"
+  "catch"
+  "finally"
+  "throw"
+] @keyword.control.exception
+
+[
+  "using"
+  "namespace"
+] @keyword.control.import
+
+[
+  "class"
+  "struct"
+  "interface"
+  "enum"
+  "delegate"
+  "event"
+] @keyword.storage.type
+
+[
+  "as"
+  "is"
+  "in"
+  "out"
+  "ref"
+  "params"
+  "new"
+  "typeof"
+  "await"
+  "lock"
+  "operator"
+  "implicit"
+  "explicit"
+  "get"
+  "set"
+  "where"
+] @keyword
+
+[
+  "="
+  "=="
+  "!="
+  "<"
+  ">"
+  "<="
+  ">="
+  "+"
+  "-"
+  "*"
+  "/"
+  "%"
+  "!"
+  "&&"
+  "||"
+  "?"
+  "??"
+  "=>"
+  "+="
+  "-="
+  "++"
+  "--"
+] @operator
+
+[
+  ";"
+  ","
+  "."
+  ":"
+] @punctuation.delimiter
+
+[
+  "("
+  ")"
+  "["
+  "]"
+  "{"
+  "}"
+] @punctuation.bracket
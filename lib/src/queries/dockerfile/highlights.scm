@@ -0,0 +1,52 @@
+; Highlights for tree-sitter-dockerfile using Helix scopes.
+
+[
+  "FROM"
+  "AS"
+  "RUN"
+  "CMD"
+  "LABEL"
+  "EXPOSE"
+  "ENV"
+  "ADD"
+  "COPY"
+  "ENTRYPOINT"
+  "VOLUME"
+  "USER"
+  "WORKDIR"
+  "ARG"
+  "ONBUILD"
+  "STOPSIGNAL"
+  "HEALTHCHECK"
+  "SHELL"
+  "MAINTAINER"
+] @keyword
+
+(comment) @comment
+
+(image_name) @type
+(image_tag) @label
+(image_alias) @label
+
+[
+  (double_quoted_string)
+  (json_string)
+] @string
+
+(param) @attribute
+
+(expansion
+  [
+    "$"
+    "{"
+    "}"
+  ] @punctuation.special)
+
+(variable) @variable
+
+[
+  ":"
+  "@"
+] @operator
+
+(line_continuation) @constant.character.escape
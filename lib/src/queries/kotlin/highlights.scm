@@ -0,0 +1,147 @@
+; Highlights for tree-sitter-kotlin using Helix scopes.
+
+[
+  (line_comment)
+  (multiline_comment)
+  (shebang_line)
+] @comment
+
+(string_literal) @string
+(character_literal) @constant.character
+[
+  (integer_literal)
+  (long_literal)
+  (hex_literal)
+  (bin_literal)
+  (unsigned_literal)
+] @constant.numeric.integer
+(real_literal) @constant.numeric.float
+(boolean_literal) @constant.builtin.boolean
+"null" @constant.builtin
+
+(this_expression) @variable.builtin
+(super_expression) @variable.builtin
+
+(annotation) @attribute
+
+(type_identifier) @type
+
+(package_header (identifier) @namespace)
+(import_header (identifier) @namespace)
+
+(function_declaration (simple_identifier) @function)
+(call_expression (simple_identifier) @function)
+(call_expression
+  (navigation_expression
+    (navigation_suffix (simple_identifier) @function.method)))
+
+(navigation_suffix (simple_identifier) @variable.other.member)
+(class_parameter (simple_identifier) @variable.other.member)
+(parameter (simple_identifier) @variable.parameter)
+
+(simple_identifier) @variable
+
+[
+  (class_modifier)
+  (member_modifier)
+  (function_modifier)
+  (property_modifier)
+  (visibility_modifier)
+  (inheritance_modifier)
+  (parameter_modifier)
+] @keyword.storage.modifier
+
+[
+  "if"
+  "else"
+  "when"
+] @keyword.control.conditional
+
+[
+  "for"
+  "while"
+  "do"
+] @keyword.control.repeat
+
+[
+  "return"
+  "break"
+  "continue"
+] @keyword.control.return
+
+[
+  "try"
+  "catch"
+  "finally"
+  "throw"
+] @keyword.control.exception
+
+[
+  "package"
+  "import"
+] @keyword.control.import
+
+[
+  "class"
+  "interface"
+  "object"
+  "typealias"
+] @keyword.storage.type
+
+"fun" @keyword.function
+
+[
+  "val"
+  "var"
+] @keyword.storage
+
+[
+  "constructor"
+  "companion"
+  "init"
+  "by"
+  "where"
+] @keyword
+
+[
+  "in"
+  "is"
+  "as"
+] @keyword.operator
+
+[
+  "="
+  "=="
+  "!="
+  "<"
+  ">"
+  "<="
+  ">="
+  "+"
+  "-"
+  "*"
+  "/"
+  "%"
+  "!"
+  "&&"
+  "||"
+  "->"
+  ".."
+  "?:"
+] @operator
+
+[
+  ";"
+  ","
+  "."
+  ":"
+] @punctuation.delimiter
+
+[
+  "("
+  ")"
+  "["
+  "]"
+  "{"
+  "}"
+] @punctuation.bracket
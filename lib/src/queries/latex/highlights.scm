@@ -0,0 +1,31 @@
+; Highlights for tree-sitter-latex using Helix scopes.
+
+(line_comment) @comment
+
+(begin
+  command: _ @function.macro
+  name: (curly_group_text (text) @label))
+
+(end
+  command: _ @function.macro
+  name: (curly_group_text (text) @label))
+
+(section
+  command: _ @namespace
+  text: (curly_group) @markup.heading)
+
+(subsection
+  command: _ @namespace
+  text: (curly_group) @markup.heading)
+
+(inline_formula) @markup.raw.inline
+(displayed_equation) @markup.raw.block
+
+(command_name) @function
+
+[
+  "["
+  "]"
+  "{"
+  "}"
+] @punctuation.bracket
@@ -0,0 +1,106 @@
+; Highlights for tree-sitter-lua using Helix scopes.
+
+(comment) @comment
+(hash_bang_line) @comment
+
+(string) @string
+(number) @constant.numeric
+(nil) @constant.builtin
+[
+  (true)
+  (false)
+] @constant.builtin.boolean
+(vararg_expression) @constant
+
+((identifier) @variable.builtin
+  (#eq? @variable.builtin "self"))
+
+((identifier) @constant
+  (#match? @constant "^[A-Z][A-Z_0-9]*$"))
+
+(function_declaration
+  name: (identifier) @function)
+(function_declaration
+  name: (dot_index_expression field: (identifier) @function))
+(function_declaration
+  name: (method_index_expression method: (identifier) @function.method))
+
+(function_call
+  name: (identifier) @function)
+(function_call
+  name: (dot_index_expression field: (identifier) @function))
+(function_call
+  name: (method_index_expression method: (identifier) @function.method))
+
+(parameters (identifier) @variable.parameter)
+
+(field name: (identifier) @variable.other.member)
+(dot_index_expression field: (identifier) @variable.other.member)
+
+(identifier) @variable
+
+[
+  "if"
+  "then"
+  "elseif"
+  "else"
+] @keyword.control.conditional
+
+[
+  "for"
+  "while"
+  "repeat"
+  "until"
+] @keyword.control.repeat
+
+(break_statement) @keyword.control.repeat
+
+"return" @keyword.control.return
+
+[
+  "function"
+  "local"
+  "do"
+  "end"
+  "in"
+] @keyword
+
+[
+  "and"
+  "or"
+  "not"
+] @keyword.operator
+
+[
+  "+"
+  "-"
+  "*"
+  "/"
+  "%"
+  "^"
+  "#"
+  "=="
+  "~="
+  "<="
+  ">="
+  "<"
+  ">"
+  "="
+  ".."
+] @operator
+
+[
+  ";"
+  ":"
+  ","
+  "."
+] @punctuation.delimiter
+
+[
+  "("
+  ")"
+  "["
+  "]"
+  "{"
+  "}"
+] @punctuation.bracket
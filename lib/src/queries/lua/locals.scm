@@ -0,0 +1,11 @@
+; Scopes and definitions for tree-sitter-lua, used to highlight references to parameters.
+
+[
+  (chunk)
+  (function_declaration)
+  (function_definition)
+] @local.scope
+
+(parameters (identifier) @local.definition)
+
+(identifier) @local.reference
@@ -1,22 +1,80 @@
-use crate::{theme, Error, Lang};
-use std::collections::HashMap;
+use crate::theme::UnderlineStyle;
+use crate::{ansi, theme, token, ClassNames, Error, Lang};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
-use tree_sitter_highlight::{Highlight, HighlightConfiguration, Highlighter, HtmlRenderer};
+use std::path::PathBuf;
+use tree_sitter_highlight::{
+    Highlight, HighlightConfiguration, HighlightEvent, Highlighter, HtmlRenderer,
+};
 
-pub(crate) const HIGHLIGHT_NAMES: [&str; 27] = [
+/// Capture names recognized by default.
+///
+/// These are the [scopes](https://docs.helix-editor.com/themes.html#syntax-highlighting) styled
+/// by Helix themes plus a few names used by the queries of the upstream tree-sitter grammars.
+pub const HIGHLIGHT_NAMES: &[&str] = &[
     "attribute",
     "comment",
+    "comment.block",
+    "comment.block.documentation",
+    "comment.line",
     "constant",
     "constant.builtin",
+    "constant.builtin.boolean",
+    "constant.character",
+    "constant.character.escape",
+    "constant.numeric",
+    "constant.numeric.float",
+    "constant.numeric.integer",
     "constructor",
+    "diff",
+    "diff.delta",
+    "diff.delta.moved",
+    "diff.minus",
+    "diff.plus",
     "escape",
     "function",
     "function.builtin",
-    "function.method",
     "function.macro",
+    "function.method",
+    "function.special",
     "include",
     "keyword",
+    "keyword.control",
+    "keyword.control.conditional",
+    "keyword.control.exception",
+    "keyword.control.import",
+    "keyword.control.repeat",
+    "keyword.control.return",
+    "keyword.directive",
+    "keyword.function",
+    "keyword.operator",
+    "keyword.storage",
+    "keyword.storage.modifier",
+    "keyword.storage.type",
     "label",
+    "markup",
+    "markup.bold",
+    "markup.heading",
+    "markup.heading.1",
+    "markup.heading.2",
+    "markup.heading.3",
+    "markup.heading.4",
+    "markup.heading.5",
+    "markup.heading.6",
+    "markup.heading.marker",
+    "markup.italic",
+    "markup.link",
+    "markup.link.label",
+    "markup.link.text",
+    "markup.link.url",
+    "markup.list",
+    "markup.list.numbered",
+    "markup.list.unnumbered",
+    "markup.quote",
+    "markup.raw",
+    "markup.raw.block",
+    "markup.raw.inline",
+    "markup.strikethrough",
     "namespace",
     "number",
     "operator",
@@ -24,109 +82,1059 @@ pub(crate) const HIGHLIGHT_NAMES: [&str; 27] = [
     "punctuation",
     "punctuation.bracket",
     "punctuation.delimiter",
+    "punctuation.special",
     "repeat",
+    "special",
     "string",
+    "string.regexp",
+    "string.special",
+    "string.special.path",
+    "string.special.symbol",
+    "string.special.url",
+    "tag",
+    "tag.builtin",
     "type",
     "type.builtin",
+    "type.enum",
+    "type.enum.variant",
+    "type.parameter",
     "variable",
     "variable.builtin",
+    "variable.other",
+    "variable.other.member",
     "variable.parameter",
 ];
 
-/// HTML syntax highlighting renderer.
+/// CSS properties set by [`css_properties()`] in the order they are declared.
+const CSS_PROPERTIES: &[&str] = &[
+    "color",
+    "background-color",
+    "font-weight",
+    "opacity",
+    "font-style",
+    "text-decoration-line",
+    "text-decoration-style",
+    "text-decoration-color",
+    "visibility",
+];
+
+/// CSS custom property holding `property` of the capture `name`, e.g. `--tsc-keyword` for the
+/// color and `--tsc-keyword-font-weight` for the weight of `keyword` with the prefix `tsc-`.
+fn css_variable(prefix: &str, name: &str, property: &str) -> String {
+    let name = name.replace('.', "-");
+
+    match property {
+        "color" => format!("--{prefix}{name}"),
+        property => format!("--{prefix}{name}-{property}"),
+    }
+}
+
+/// CSS properties and their values for `style`. Reversed styles fall back to the colors of
+/// `theme` to swap. Blinking is not supported by browsers and thus ignored.
+fn css_properties(style: &theme::Style, theme: &theme::Theme) -> Vec<(&'static str, String)> {
+    let mut properties = Vec::new();
+
+    let (color, background) = if style.is_reversed {
+        (
+            Some(style.background.as_ref().unwrap_or(&theme.background)),
+            Some(style.color.as_ref().unwrap_or(&theme.foreground)),
+        )
+    } else {
+        (style.color.as_ref(), style.background.as_ref())
+    };
+
+    if let Some(color) = color {
+        properties.push(("color", color.clone()));
+    }
+
+    if let Some(background) = background {
+        properties.push(("background-color", background.clone()));
+    }
+
+    if style.is_bold {
+        properties.push(("font-weight", "bold".into()));
+    }
+
+    if style.is_dim {
+        properties.push(("opacity", "0.6".into()));
+    }
+
+    if style.is_italic {
+        properties.push(("font-style", "italic".into()));
+    }
+
+    let lines = match (style.is_underlined, style.is_crossed_out) {
+        (true, true) => Some("underline line-through"),
+        (true, false) => Some("underline"),
+        (false, true) => Some("line-through"),
+        (false, false) => None,
+    };
+
+    if let Some(lines) = lines {
+        properties.push(("text-decoration-line", lines.into()));
+    }
+
+    if style.is_underlined {
+        let shape = match style.underline_style {
+            UnderlineStyle::Line => None,
+            UnderlineStyle::Curl => Some("wavy"),
+            UnderlineStyle::Dashed => Some("dashed"),
+            UnderlineStyle::Dotted => Some("dotted"),
+            UnderlineStyle::DoubleLine => Some("double"),
+        };
+
+        if let Some(shape) = shape {
+            properties.push(("text-decoration-style", shape.into()));
+        }
+
+        if let Some(color) = &style.underline_color {
+            properties.push(("text-decoration-color", color.clone()));
+        }
+    }
+
+    if style.is_hidden {
+        properties.push(("visibility", "hidden".into()));
+    }
+
+    properties
+}
+
+/// CSS declarations for `style`, e.g. `color: #f00;font-weight: bold;`.
+fn css_declarations(style: &theme::Style, theme: &theme::Theme) -> String {
+    let mut declarations = String::new();
+
+    for (property, value) in css_properties(style, theme) {
+        let _ = write!(declarations, "{property}: {value};");
+    }
+
+    declarations
+}
+
+/// Remove whitespace that is not needed to separate tokens and the last semicolon of each block
+/// from `css`. Quoted strings are copied unchanged.
+fn minify(css: &str) -> String {
+    let mut minified = String::with_capacity(css.len());
+    let mut space = false;
+    let mut chars = css.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+
+        if space
+            && !matches!(c, '{' | '}' | ';' | ',')
+            && !minified.is_empty()
+            && !minified.ends_with(['{', '}', ';', ',', ':'])
+        {
+            minified.push(' ');
+        }
+
+        if c == '}' && minified.ends_with(';') {
+            minified.pop();
+        }
+
+        minified.push(c);
+        space = false;
+
+        if c == '"' || c == '\'' {
+            while let Some(quoted) = chars.next() {
+                minified.push(quoted);
+
+                match quoted {
+                    '\\' => minified.extend(chars.next()),
+                    _ if quoted == c => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    minified
+}
+
+/// Selector of elements with the space-separated `classes` within the CSS `scope`.
+fn selector(scope: Option<&str>, classes: &str) -> String {
+    let classes = classes.replace(' ', ".");
+
+    match scope {
+        Some(scope) => format!("{scope} .{classes}"),
+        None => format!(".{classes}"),
+    }
+}
+
+/// Selector of the element carrying the custom properties of the theme selected by the `theme`
+/// selector, or of the default theme. Themes apply to the root element unless the CSS is scoped,
+/// in which case `theme` must match the `scope` or one of its ancestors.
+fn variables_selector(scope: Option<&str>, theme: Option<&str>) -> String {
+    match (scope, theme) {
+        (None, None) => ":root".to_string(),
+        (None, Some(theme)) => theme.to_string(),
+        (Some(scope), None) => scope.to_string(),
+        (Some(scope), Some(theme)) => format!("{theme} {scope}, {scope}{theme}"),
+    }
+}
+
+/// Indices of the captures highlighted by `events`.
+fn captures(events: &[HighlightEvent]) -> HashSet<usize> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            HighlightEvent::HighlightStart(highlight) => Some(highlight.0),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Escape the characters of `text` that have a special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Builder to configure a [`Renderer`].
+pub struct RendererBuilder {
+    theme: theme::Theme,
+    dark_theme: Option<theme::Theme>,
+    scoped_themes: Vec<(String, theme::Theme)>,
+    css_variables: bool,
+    names: Vec<String>,
+    class_names: ClassNames,
+    class_prefix: String,
+    css_scope: Option<String>,
+    minify_css: bool,
+    inline_styles: bool,
+    runtime: Option<PathBuf>,
+}
+
+impl RendererBuilder {
+    /// Recognize `names` as capture names instead of the default [`HIGHLIGHT_NAMES`].
+    ///
+    /// Captures of a grammar's highlight query are mapped to the recognized name matching most
+    /// of their dotted parts, styles are looked up in the theme with the same fallback rules as
+    /// [`Theme::from_helix()`](crate::Theme::from_helix).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let theme = tree_painter::Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let renderer = tree_painter::Renderer::builder(theme)
+    ///     .highlight_names(["keyword", "string", "string.special", "markup.heading"])
+    ///     .build();
+    ///
+    /// assert!(renderer.css().contains(".tsc-string-special"));
+    /// ```
+    pub fn highlight_names<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.names = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Name CSS classes following `class_names`, e.g. to use existing highlight.js or Pygments
+    /// stylesheets instead of [`Renderer::css()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{themes, ClassNames, Lang, Renderer, Theme};
+    ///
+    /// let theme = Theme::from_helix(themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = Renderer::builder(theme)
+    ///     .class_names(ClassNames::HighlightJs)
+    ///     .build();
+    ///
+    /// let line = renderer.render(&Lang::Rust, b"fn main() {}").unwrap().next().unwrap();
+    /// assert!(line.starts_with(r#"<span class="hljs-keyword">fn</span>"#));
+    /// ```
+    pub fn class_names(mut self, class_names: ClassNames) -> Self {
+        self.class_names = class_names;
+        self
+    }
+
+    /// Prepend `prefix` instead of `tsc-` to the CSS classes and custom properties.
+    ///
+    /// The classes of [`ClassNames::HighlightJs`] and [`ClassNames::Pygments`] are not prefixed.
+    pub fn class_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.class_prefix = prefix.into();
+        self
+    }
+
+    /// Restrict the rules of [`Renderer::css()`] to elements below those matching `selector`,
+    /// whose custom properties then replace those of the root element. This allows putting code
+    /// highlighted with different themes on the same page.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let theme = tree_painter::Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let renderer = tree_painter::Renderer::builder(theme)
+    ///     .css_scope(".code-block-42")
+    ///     .build();
+    ///
+    /// assert!(renderer.css().contains(".code-block-42 .tsc-keyword { color: #cba6f7;}"));
+    /// ```
+    pub fn css_scope<S: Into<String>>(mut self, selector: S) -> Self {
+        self.css_scope = Some(selector.into());
+        self
+    }
+
+    /// Remove all optional whitespace from the output of [`Renderer::css()`] and
+    /// [`Renderer::used_css()`].
+    pub fn minify_css(mut self, minify_css: bool) -> Self {
+        self.minify_css = minify_css;
+        self
+    }
+
+    /// Emit `style` attributes carrying the theme's colors and modifiers instead of CSS classes.
+    ///
+    /// The rendered HTML is self-contained and does not need [`Renderer::css()`], which is useful
+    /// for emails, feeds or rich text editors that strip classes. The theme's foreground and
+    /// background colors still have to be applied to the surrounding element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let theme = tree_painter::Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = tree_painter::Renderer::builder(theme)
+    ///     .inline_styles(true)
+    ///     .build();
+    ///
+    /// let line = renderer.render(&tree_painter::Lang::Rust, b"fn main() {}").unwrap().next().unwrap();
+    /// assert!(line.starts_with(r#"<span style="color: #cba6f7;">fn</span>"#));
+    /// ```
+    pub fn inline_styles(mut self, inline_styles: bool) -> Self {
+        self.inline_styles = inline_styles;
+        self
+    }
+
+    /// Pair the renderer's theme, used as the light theme, with a `dark` theme.
+    ///
+    /// [`Renderer::css()`] then emits both themes as CSS custom properties, switching to `dark`
+    /// if the user prefers a dark color scheme or an element sets `data-theme="dark"`, while
+    /// `data-theme="light"` keeps the light theme. The rendered HTML is the same for both themes.
+    /// The dark theme is not used for inline styles, ANSI output or tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{themes, Renderer, Theme};
+    ///
+    /// let light = Theme::from_helix(themes::CATPPUCCIN_LATTE).unwrap();
+    /// let dark = Theme::from_helix(themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let renderer = Renderer::builder(light).dark_theme(dark).build();
+    /// let css = renderer.css();
+    ///
+    /// assert!(css.contains("@media (prefers-color-scheme: dark)"));
+    /// assert!(css.contains(".tsc-keyword { color: var(--tsc-keyword); }"));
+    /// ```
+    pub fn dark_theme(mut self, dark: theme::Theme) -> Self {
+        self.dark_theme = Some(dark);
+        self
+    }
+
+    /// Emit the theme's colors and modifiers as CSS custom properties of the root element, which
+    /// the rules of [`Renderer::css()`] refer to, e.g. `color: var(--tsc-keyword)`.
+    ///
+    /// Pages can then restyle the code by overriding the custom properties. Pairing themes with
+    /// [`RendererBuilder::dark_theme()`] or [`RendererBuilder::scoped_theme()`] implies this.
+    /// Classes shared by several capture names refer to the properties of the first of them.
+    pub fn css_variables(mut self, css_variables: bool) -> Self {
+        self.css_variables = css_variables;
+        self
+    }
+
+    /// Add `theme` whose custom properties [`Renderer::css()`] scopes under the CSS `selector`,
+    /// so that adding e.g. the class of `selector` to an element switches all code below it to
+    /// `theme`. Can be called multiple times to add any number of themes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{themes, Renderer, Theme};
+    ///
+    /// let mocha = Theme::from_helix(themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let latte = Theme::from_helix(themes::CATPPUCCIN_LATTE).unwrap();
+    /// let ayu = Theme::from_helix(themes::AYU_LIGHT).unwrap();
+    ///
+    /// let renderer = Renderer::builder(mocha)
+    ///     .scoped_theme(".latte", latte)
+    ///     .scoped_theme(".ayu-light", ayu)
+    ///     .build();
+    /// let css = renderer.css();
+    ///
+    /// assert!(css.contains(":root { --tsc-main-fg-color: #cdd6f4;"));
+    /// assert!(css.contains(".latte { --tsc-main-fg-color: #4c4f69;"));
+    /// assert!(css.contains(".ayu-light { --tsc-main-fg-color: #5c6166;"));
+    /// ```
+    pub fn scoped_theme<S: Into<String>>(mut self, selector: S, theme: theme::Theme) -> Self {
+        self.scoped_themes.push((selector.into(), theme));
+        self
+    }
+
+    /// Load highlights, injections and locals queries from a Helix-style runtime query directory
+    /// such as `helix/runtime/queries` instead of using the queries bundled with the grammars.
+    ///
+    /// Queries of a language are read from the directory named like the language in Helix, e.g.
+    /// `rust` or `c-sharp`, and `; inherits: ecma` directives are resolved. Languages without a
+    /// highlights query in `runtime` fall back to the bundled queries.
+    ///
+    /// `#any-of?` and `#not-any-of?` predicates are evaluated, rendering fails with
+    /// [`Error::UnsupportedPredicate`](crate::Error::UnsupportedPredicate) for queries using
+    /// other predicates that tree-sitter-highlight cannot evaluate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{themes, Lang, Renderer, Theme};
+    ///
+    /// let runtime = std::env::temp_dir().join("tree-painter-any-of");
+    /// std::fs::create_dir_all(runtime.join("rust")).unwrap();
+    /// std::fs::write(
+    ///     runtime.join("rust/highlights.scm"),
+    ///     r#"((identifier) @constant (#any-of? @constant "Some" "None"))"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// let theme = Theme::from_helix(themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = Renderer::builder(theme).helix_runtime(&runtime).build();
+    /// let tokens = renderer.tokens(&Lang::Rust, b"let x = None;").unwrap();
+    ///
+    /// let constants = tokens
+    ///     .iter()
+    ///     .filter(|token| token.capture == Some("constant"))
+    ///     .map(|token| token.range.clone())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(constants, vec![8..12]);
+    /// ```
+    pub fn helix_runtime<P: Into<PathBuf>>(mut self, runtime: P) -> Self {
+        self.runtime = Some(runtime.into());
+        self
+    }
+
+    /// Build the renderer.
+    pub fn build(self) -> Renderer {
+        let mut attributes = HashMap::default();
+        let mut styles = HashMap::default();
+        let mut classes = HashMap::default();
+
+        for (index, name) in self.names.iter().enumerate() {
+            let style = self.theme.resolve(name);
+            let styled_elsewhere = self
+                .dark_theme
+                .iter()
+                .chain(self.scoped_themes.iter().map(|(_, theme)| theme))
+                .any(|theme| theme.resolve(name).is_some());
+
+            if let Some(style) = style {
+                styles.insert(index, style.clone());
+            }
+
+            if let (Some(style), true) = (style, self.inline_styles) {
+                let declarations = escape_html(&css_declarations(style, &self.theme));
+                attributes.insert(index, format!(r#"style="{declarations}""#));
+                continue;
+            }
+
+            if style.is_none() && (!styled_elsewhere || self.inline_styles) {
+                continue;
+            }
+
+            if let Some(class) = self.class_names.class(name, &self.class_prefix) {
+                attributes.insert(index, format!(r#"class="{class}""#));
+                classes.insert(index, class);
+            }
+        }
+
+        let css_variables =
+            self.css_variables || self.dark_theme.is_some() || !self.scoped_themes.is_empty();
+
+        Renderer {
+            renderer: HtmlRenderer::new(),
+            theme: self.theme,
+            dark_theme: self.dark_theme,
+            scoped_themes: self.scoped_themes,
+            css_variables,
+            names: self.names,
+            prefix: self.class_prefix,
+            scope: self.css_scope,
+            minify_css: self.minify_css,
+            inline_styles: self.inline_styles,
+            styles,
+            classes,
+            attributes,
+            used: HashSet::default(),
+            configs: HashMap::default(),
+            registered: HashMap::default(),
+            runtime: self.runtime,
+        }
+    }
+}
+
+/// Syntax highlighting renderer producing HTML or text with ANSI escape sequences.
 pub struct Renderer {
     renderer: HtmlRenderer,
     theme: theme::Theme,
-    css_classes: HashMap<usize, String>,
+    dark_theme: Option<theme::Theme>,
+    scoped_themes: Vec<(String, theme::Theme)>,
+    css_variables: bool,
+    names: Vec<String>,
+    prefix: String,
+    scope: Option<String>,
+    minify_css: bool,
+    inline_styles: bool,
+    styles: HashMap<usize, theme::Style>,
+    classes: HashMap<usize, String>,
+    attributes: HashMap<usize, String>,
+    /// Indices of the captures rendered to HTML so far.
+    used: HashSet<usize>,
     configs: HashMap<Lang, HighlightConfiguration>,
+    registered: HashMap<String, Lang>,
+    runtime: Option<PathBuf>,
 }
 
 impl Renderer {
     /// Create a new renderer based on `theme`.
     pub fn new(theme: theme::Theme) -> Self {
-        let mut css_classes = HashMap::default();
-
-        for index in theme.style_map.keys() {
-            css_classes.insert(
-                *index,
-                format!(r#"class="tsc-{}""#, HIGHLIGHT_NAMES[*index]),
-            );
-        }
+        Self::builder(theme).build()
+    }
 
-        Self {
-            renderer: HtmlRenderer::new(),
+    /// Create a builder for a renderer based on `theme`.
+    pub fn builder(theme: theme::Theme) -> RendererBuilder {
+        RendererBuilder {
             theme,
-            css_classes,
-            configs: HashMap::default(),
+            dark_theme: None,
+            scoped_themes: Vec::new(),
+            css_variables: false,
+            names: HIGHLIGHT_NAMES
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            class_names: ClassNames::default(),
+            class_prefix: "tsc-".to_string(),
+            css_scope: None,
+            minify_css: false,
+            inline_styles: false,
+            runtime: None,
         }
     }
 
     /// Generate CSS block to be included in the `<style></style>` block or in an external CSS
     /// file.
     pub fn css(&self) -> String {
+        self.stylesheet(None, self.scope.as_deref())
+    }
+
+    /// Generate CSS like [`Renderer::css()`] but only with the rules for captures that occurred in
+    /// the output of [`Renderer::render()`] since the renderer was created or
+    /// [`Renderer::clear_used()`] was called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{themes, Lang, Renderer, Theme};
+    ///
+    /// let theme = Theme::from_helix(themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = Renderer::builder(theme).minify_css(true).build();
+    /// let html = renderer.render(&Lang::Rust, b"fn main() {}").unwrap().collect::<String>();
+    /// let css = renderer.used_css();
+    ///
+    /// assert!(css.contains(".tsc-keyword{color:#cba6f7}"));
+    /// assert!(!css.contains(".tsc-string"));
+    /// ```
+    pub fn used_css(&self) -> String {
+        self.stylesheet(Some(&self.used), self.scope.as_deref())
+    }
+
+    /// Forget the captures used so far by [`Renderer::used_css()`].
+    pub fn clear_used(&mut self) {
+        self.used.clear();
+    }
+
+    /// CSS with all rules or only those for the `used` captures within the CSS `scope`, minified
+    /// if configured.
+    fn stylesheet(&self, used: Option<&HashSet<usize>>, scope: Option<&str>) -> String {
+        let css = if self.css_variables {
+            self.variables_css(used, scope)
+        } else {
+            self.plain_css(used, scope)
+        };
+
+        if self.minify_css {
+            minify(&css)
+        } else {
+            css
+        }
+    }
+
+    /// CSS for the renderer's theme with its colors and modifiers set by each rule.
+    fn plain_css(&self, used: Option<&HashSet<usize>>, scope: Option<&str>) -> String {
         let mut css = String::new();
 
         let _ = writeln!(
             css,
-            ":root {{ --tsc-main-fg-color: {}; --tsc-main-bg-color: {}; }}",
-            self.theme.foreground.color, self.theme.background.color
+            "{} {{ --{prefix}main-fg-color: {}; --{prefix}main-bg-color: {}; }}",
+            variables_selector(scope, None),
+            self.theme.foreground,
+            self.theme.background,
+            prefix = self.prefix,
         );
 
-        for (index, style) in &self.theme.style_map {
-            let _ = write!(
-                css,
-                ".tsc-{} {{ color: {};",
-                HIGHLIGHT_NAMES[*index], style.color
+        for (index, _, selector) in self.rules(used, scope) {
+            if let Some(style) = self.styles.get(&index) {
+                let _ = writeln!(
+                    css,
+                    "{selector} {{ {}}}",
+                    css_declarations(style, &self.theme)
+                );
+            }
+        }
+
+        self.write_base_rules(&mut css, scope);
+        css
+    }
+
+    /// CSS for all themes of the renderer, whose colors and modifiers are custom properties of
+    /// the root element, an element with a `data-theme` attribute or the scope of the theme.
+    fn variables_css(&self, used: Option<&HashSet<usize>>, scope: Option<&str>) -> String {
+        let themes = std::iter::once(&self.theme)
+            .chain(&self.dark_theme)
+            .chain(self.scoped_themes.iter().map(|(_, theme)| theme))
+            .collect::<Vec<_>>();
+
+        // Properties of each rule set by any of the themes.
+        let rules = self
+            .rules(used, scope)
+            .into_iter()
+            .map(|(_, name, selector)| {
+                let mut properties = themes
+                    .iter()
+                    .filter_map(|theme| Some((theme.resolve(name)?, theme)))
+                    .flat_map(|(style, theme)| css_properties(style, theme))
+                    .map(|(property, _)| property)
+                    .collect::<Vec<_>>();
+
+                properties.sort_by_key(|property| {
+                    CSS_PROPERTIES.iter().position(|known| known == property)
+                });
+                properties.dedup();
+                (name, selector, properties)
+            })
+            .collect::<Vec<_>>();
+
+        let variables = |theme: &theme::Theme| {
+            let mut variables = format!(
+                "--{prefix}main-fg-color: {}; --{prefix}main-bg-color: {}; ",
+                theme.foreground,
+                theme.background,
+                prefix = self.prefix,
             );
 
-            if style.is_bold {
-                css.push_str("font-weight: bold;");
+            for (name, _, properties) in &rules {
+                let values = theme
+                    .resolve(name)
+                    .map(|style| css_properties(style, theme))
+                    .unwrap_or_default();
+
+                for property in properties {
+                    // Unset properties must not inherit the value of an enclosing theme.
+                    let value = values
+                        .iter()
+                        .find(|(set, _)| set == property)
+                        .map_or("initial", |(_, value)| value.as_str());
+
+                    let variable = css_variable(&self.prefix, name, property);
+                    let _ = write!(variables, "{variable}: {value}; ");
+                }
             }
 
-            if style.is_italic {
-                css.push_str("font-style: italic;");
+            variables
+        };
+
+        let mut css = String::new();
+        let root = variables_selector(scope, None);
+        let _ = writeln!(css, "{root} {{ {}}}", variables(&self.theme));
+
+        if let Some(dark) = &self.dark_theme {
+            let light_selector = variables_selector(scope, Some(r#"[data-theme="light"]"#));
+            let dark_selector = variables_selector(scope, Some(r#"[data-theme="dark"]"#));
+
+            let _ = writeln!(
+                css,
+                "@media (prefers-color-scheme: dark) {{ {root} {{ {}}} }}",
+                variables(dark)
+            );
+            let _ = writeln!(css, "{light_selector} {{ {}}}", variables(&self.theme));
+            let _ = writeln!(css, "{dark_selector} {{ {}}}", variables(dark));
+        }
+
+        for (selector, theme) in &self.scoped_themes {
+            let selector = variables_selector(scope, Some(selector));
+            let _ = writeln!(css, "{selector} {{ {}}}", variables(theme));
+        }
+
+        for (name, selector, properties) in &rules {
+            let _ = write!(css, "{selector} {{ ");
+
+            for property in properties {
+                let variable = css_variable(&self.prefix, name, property);
+                let _ = write!(css, "{property}: var({variable}); ");
             }
 
             css.push_str("}\n");
         }
 
-        css.push_str(".tsc-line { word-wrap: normal; white-space: pre; }\n");
+        self.write_base_rules(&mut css, scope);
         css
     }
 
+    /// Write the rules applying the main colors to the background class and formatting lines
+    /// within the CSS `scope`.
+    fn write_base_rules(&self, css: &mut String, scope: Option<&str>) {
+        let _ = writeln!(
+            css,
+            "{} {{ color: var(--{prefix}main-fg-color); background-color: var(--{prefix}main-bg-color); }}",
+            selector(scope, &format!("{}bg", self.prefix)),
+            prefix = self.prefix,
+        );
+        let _ = writeln!(
+            css,
+            "{} {{ word-wrap: normal; white-space: pre; }}",
+            selector(scope, &format!("{}line", self.prefix))
+        );
+    }
+
+    /// Index, capture name and selector within the CSS `scope` of each CSS rule, one for each
+    /// class in the order of the capture names, optionally only for classes of `used` captures.
+    fn rules(
+        &self,
+        used: Option<&HashSet<usize>>,
+        scope: Option<&str>,
+    ) -> Vec<(usize, &str, String)> {
+        let mut seen = HashSet::new();
+
+        let used = used.map(|used| {
+            used.iter()
+                .filter_map(|index| self.classes.get(index))
+                .collect::<HashSet<_>>()
+        });
+
+        self.names
+            .iter()
+            .enumerate()
+            .filter_map(|(index, name)| Some((index, name, self.classes.get(&index)?)))
+            .filter(|(_, _, class)| seen.insert(class.as_str()))
+            .filter(|(_, _, class)| match &used {
+                Some(used) => used.contains(class),
+                None => true,
+            })
+            .map(|(index, name, class)| (index, name.as_str(), selector(scope, class)))
+            .collect()
+    }
+
     /// Render `source` based on the `lang`.
+    ///
+    /// Languages injected into `source`, for example JavaScript inside a Markdown code fence, are
+    /// resolved with [`Lang::from_name()`] or among registered grammars and rendered with their
+    /// own highlighting.
     pub fn render<'a>(
         &'a mut self,
         lang: &Lang,
         source: &[u8],
     ) -> Result<impl Iterator<Item = &'a str>, Error> {
-        fn foo<'a>(_: &str) -> Option<&'a HighlightConfiguration> {
-            None
-        }
-
-        let config = match self.configs.get(lang) {
-            Some(config) => config,
-            None => {
-                let mut config = lang.config();
-                config.configure(&HIGHLIGHT_NAMES);
-                self.configs.insert(lang.clone(), config);
-                self.configs.get(lang).unwrap()
-            }
-        };
-
-        let mut highlighter = Highlighter::new();
-        let events = highlighter.highlight(config, source, None, foo)?;
+        let events = self.highlight(lang, source)?;
+        self.render_events(events, source)
+    }
 
+    /// Render the highlight `events` of `source` and mark their captures as used.
+    fn render_events<'a>(
+        &'a mut self,
+        events: Vec<HighlightEvent>,
+        source: &[u8],
+    ) -> Result<impl Iterator<Item = &'a str>, Error> {
+        self.used.extend(captures(&events));
         self.renderer.reset();
         self.renderer.render(
-            events,
+            events.into_iter().map(Ok),
             source,
-            &|attr: Highlight| match self.css_classes.get(&attr.0) {
-                Some(class) => class.as_bytes(),
+            &|attr: Highlight| match self.attributes.get(&attr.0) {
+                Some(attribute) => attribute.as_bytes(),
                 None => "".as_bytes(),
             },
         )?;
 
         Ok(self.renderer.lines())
     }
+
+    /// Render `source` based on `lang` as a `<pre><code>` fragment with the theme's foreground
+    /// and background colors, using the `tsc-bg` class of [`Renderer::css()`] or inline styles.
+    /// The `<code>` element has a class naming the language, e.g. `language-rust`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{themes, Lang, Renderer, Theme};
+    ///
+    /// let theme = Theme::from_helix(themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = Renderer::new(theme);
+    /// let html = renderer.render_fragment(&Lang::Rust, b"fn main() {}\n").unwrap();
+    ///
+    /// assert!(html.starts_with(r#"<pre class="tsc-bg"><code class="language-rust"><span"#));
+    /// assert!(html.ends_with("</code></pre>"));
+    /// ```
+    pub fn render_fragment(&mut self, lang: &Lang, source: &[u8]) -> Result<String, Error> {
+        let events = self.highlight(lang, source)?;
+        self.fragment(lang, events, source)
+    }
+
+    /// Render the highlight `events` of `source` as `<pre><code>` fragment.
+    fn fragment(
+        &mut self,
+        lang: &Lang,
+        events: Vec<HighlightEvent>,
+        source: &[u8],
+    ) -> Result<String, Error> {
+        let background = self.background_attribute();
+        let code = self.render_events(events, source)?.collect::<String>();
+
+        Ok(format!(
+            r#"<pre {background}><code class="language-{}">{code}</code></pre>"#,
+            escape_html(lang.helix_name())
+        ))
+    }
+
+    /// Render `source` based on `lang` as a complete HTML5 document with the UTF-8 charset and
+    /// `title`, embedding the fragment of [`Renderer::render_fragment()`] and the CSS for the
+    /// captures occurring in it.
+    ///
+    /// The embedded CSS is not restricted by [`RendererBuilder::css_scope()`], so that it applies
+    /// to the document. Captures of the document still count as used by [`Renderer::used_css()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{themes, Lang, Renderer, Theme};
+    ///
+    /// let theme = Theme::from_helix(themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = Renderer::new(theme);
+    /// let html = renderer.render_document(&Lang::Rust, b"fn main() {}\n", "main.rs").unwrap();
+    ///
+    /// assert!(html.starts_with("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">"));
+    /// assert!(html.contains("<title>main.rs</title>"));
+    /// assert!(html.contains(".tsc-bg { color: var(--tsc-main-fg-color);"));
+    /// assert!(!html.contains(".tsc-string"));
+    /// ```
+    pub fn render_document(
+        &mut self,
+        lang: &Lang,
+        source: &[u8],
+        title: &str,
+    ) -> Result<String, Error> {
+        let events = self.highlight(lang, source)?;
+        let css = self.stylesheet(Some(&captures(&events)), None);
+        let fragment = self.fragment(lang, events, source)?;
+        let background = self.background_attribute();
+        let mut document = String::new();
+
+        document.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        let _ = writeln!(document, "<title>{}</title>", escape_html(title));
+
+        if !self.inline_styles {
+            let _ = writeln!(document, "<style>\n{css}</style>");
+        }
+
+        let _ = writeln!(
+            document,
+            "</head>\n<body {background}>\n{fragment}\n</body>\n</html>"
+        );
+        Ok(document)
+    }
+
+    /// Attribute applying the theme's foreground and background colors.
+    fn background_attribute(&self) -> String {
+        if self.inline_styles {
+            format!(
+                r#"style="color: {};background-color: {};""#,
+                escape_html(&self.theme.foreground),
+                escape_html(&self.theme.background)
+            )
+        } else {
+            format!(r#"class="{}bg""#, self.prefix)
+        }
+    }
+
+    /// Render `source` based on `lang` as text colored with ANSI escape sequences, e.g. for
+    /// printing to a terminal.
+    ///
+    /// Colors are downsampled to the closest color available with `depth`. Unlike
+    /// [`Renderer::render()`] the theme's foreground and background colors are not applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{ColorDepth, Lang, Renderer, Theme};
+    ///
+    /// let theme = Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = Renderer::new(theme);
+    /// let output = renderer.render_ansi(&Lang::Rust, b"fn main() {}", ColorDepth::Ansi256).unwrap();
+    ///
+    /// assert!(output.starts_with("\x1b[38;5;"));
+    /// ```
+    pub fn render_ansi(
+        &mut self,
+        lang: &Lang,
+        source: &[u8],
+        depth: ansi::ColorDepth,
+    ) -> Result<String, Error> {
+        let events = self.highlight(lang, source)?;
+        Ok(ansi::render(
+            &events,
+            source,
+            |highlight| self.styles.get(&highlight.0),
+            depth,
+        ))
+    }
+
+    /// Register a grammar loaded at runtime so that it is used for languages injected with its
+    /// name. Registered grammars take precedence over built-in languages of the same name.
+    #[cfg(feature = "dynamic")]
+    pub fn register(&mut self, grammar: crate::Grammar) {
+        self.registered
+            .insert(grammar.name().to_ascii_lowercase(), Lang::Dynamic(grammar));
+    }
+
+    /// Highlight `source` based on `lang` and return the highlighted regions as tokens.
+    ///
+    /// Unlike the other render functions, this does not produce any particular output format
+    /// and can be used to build custom outputs or to analyze source code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Lang, Renderer, Theme};
+    ///
+    /// let theme = Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let mut renderer = Renderer::new(theme);
+    /// let tokens = renderer.tokens(&Lang::Rust, b"fn main() {}").unwrap();
+    ///
+    /// assert_eq!(tokens[0].range, 0..2);
+    /// assert_eq!(tokens[0].capture, Some("keyword"));
+    /// assert_eq!(tokens[0].style.unwrap().color.as_deref(), Some("#cba6f7"));
+    /// ```
+    pub fn tokens(&mut self, lang: &Lang, source: &[u8]) -> Result<Vec<token::Token<'_>>, Error> {
+        let events = self.highlight(lang, source)?;
+
+        Ok(token::collect(&events, source, |highlight| {
+            (
+                self.names[highlight.0].as_str(),
+                self.styles.get(&highlight.0),
+            )
+        }))
+    }
+
+    /// Highlight `source` and collect the resulting events.
+    ///
+    /// Configurations of injected languages are built lazily: if the injection callback asks for
+    /// a language that is not in the cache yet, it is loaded and `source` is highlighted again.
+    fn highlight(&mut self, lang: &Lang, source: &[u8]) -> Result<Vec<HighlightEvent>, Error> {
+        self.load_config(lang)?;
+
+        let mut highlighter = Highlighter::new();
+
+        loop {
+            let mut missing = Vec::new();
+            let configs = &self.configs;
+            let registered = &self.registered;
+
+            let events = highlighter
+                .highlight(&configs[lang], source, None, |name| {
+                    let lang = registered
+                        .get(&name.to_ascii_lowercase())
+                        .cloned()
+                        .or_else(|| Lang::from_name(name))?;
+                    let config = configs.get(&lang);
+
+                    if config.is_none() {
+                        missing.push(lang);
+                    }
+
+                    config
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if missing.is_empty() {
+                return Ok(events);
+            }
+
+            for lang in &missing {
+                self.load_config(lang)?;
+            }
+        }
+    }
+
+    /// Build and cache the highlight configuration for `lang` unless it is already cached.
+    fn load_config(&mut self, lang: &Lang) -> Result<(), Error> {
+        if !self.configs.contains_key(lang) {
+            let mut config = match &self.runtime {
+                Some(runtime) => lang.runtime_config(runtime)?,
+                None => lang.config(),
+            };
+
+            config.configure(&self.names);
+            self.configs.insert(lang.clone(), config);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minify_whitespace() {
+        assert_eq!(
+            minify(".a, .b {\n  color: #fff;\n  font-weight: bold;\n}\n\n.c .d { color: red; }\n"),
+            ".a,.b{color:#fff;font-weight:bold}.c .d{color:red}"
+        );
+    }
+
+    #[test]
+    fn minify_keeps_quoted_strings() {
+        assert_eq!(
+            minify("[data-theme=\"a  b\"] .x {\n  content: 'x ; }';\n}\n"),
+            "[data-theme=\"a  b\"] .x{content:'x ; }'}"
+        );
+        assert_eq!(
+            minify(r#"[title="a \" { b"]  { color: red; }"#),
+            r#"[title="a \" { b"]{color:red}"#
+        );
+    }
+
+    #[cfg(feature = "tree-sitter-rust")]
+    #[test]
+    fn document_css_is_unscoped() {
+        let theme = crate::Theme::from_helix(r##""keyword" = "#ff0000""##).unwrap();
+        let mut renderer = Renderer::builder(theme)
+            .css_scope(".code")
+            .minify_css(true)
+            .build();
+
+        let html = renderer
+            .render_document(&Lang::Rust, b"fn main() {}\n", "main.rs")
+            .unwrap();
+
+        assert!(html.contains(".tsc-keyword{color:#ff0000}"));
+        assert!(!html.contains(".code"));
+        assert!(renderer
+            .used_css()
+            .contains(".code .tsc-keyword{color:#ff0000}"));
+    }
 }
@@ -0,0 +1,254 @@
+use crate::color;
+use crate::theme::Style;
+use std::collections::HashMap;
+
+/// Capture names and the TextMate scopes whose styles they take when importing `.tmTheme` and
+/// VS Code themes, the preferred scope first.
+pub const TEXTMATE_SCOPES: &[(&str, &[&str])] = &[
+    ("attribute", &["entity.other.attribute-name"]),
+    ("comment", &["comment"]),
+    (
+        "comment.block.documentation",
+        &["comment.block.documentation"],
+    ),
+    ("constant", &["constant", "variable.other.constant"]),
+    ("constant.builtin", &["constant.language"]),
+    ("constant.character", &["constant.character"]),
+    ("constant.character.escape", &["constant.character.escape"]),
+    ("constant.numeric", &["constant.numeric"]),
+    (
+        "constructor",
+        &["entity.name.function.constructor", "entity.name.class"],
+    ),
+    ("function", &["entity.name.function", "support.function"]),
+    ("function.builtin", &["support.function"]),
+    (
+        "function.macro",
+        &[
+            "entity.name.function.macro",
+            "entity.name.function.preprocessor",
+        ],
+    ),
+    (
+        "function.method",
+        &["entity.name.function.member", "entity.name.function"],
+    ),
+    ("keyword", &["keyword"]),
+    ("keyword.control", &["keyword.control"]),
+    (
+        "keyword.control.conditional",
+        &["keyword.control.conditional"],
+    ),
+    (
+        "keyword.control.exception",
+        &["keyword.control.exception", "keyword.control.trycatch"],
+    ),
+    ("keyword.control.import", &["keyword.control.import"]),
+    ("keyword.control.repeat", &["keyword.control.loop"]),
+    (
+        "keyword.control.return",
+        &["keyword.control.return", "keyword.control.flow"],
+    ),
+    (
+        "keyword.directive",
+        &["keyword.other.directive", "meta.preprocessor"],
+    ),
+    (
+        "keyword.function",
+        &["storage.type.function", "keyword.function"],
+    ),
+    (
+        "keyword.operator",
+        &["keyword.operator.word", "keyword.operator"],
+    ),
+    ("keyword.storage", &["storage"]),
+    ("keyword.storage.modifier", &["storage.modifier"]),
+    ("keyword.storage.type", &["storage.type"]),
+    ("label", &["entity.name.label"]),
+    (
+        "namespace",
+        &["entity.name.namespace", "entity.name.module"],
+    ),
+    ("operator", &["keyword.operator"]),
+    ("punctuation", &["punctuation"]),
+    (
+        "punctuation.bracket",
+        &["punctuation.bracket", "punctuation.section"],
+    ),
+    (
+        "punctuation.delimiter",
+        &["punctuation.separator", "punctuation.terminator"],
+    ),
+    ("punctuation.special", &["punctuation.section.embedded"]),
+    ("string", &["string"]),
+    ("string.regexp", &["string.regexp"]),
+    ("string.special", &["string.other", "constant.other.symbol"]),
+    ("string.special.symbol", &["constant.other.symbol"]),
+    ("string.special.url", &["markup.underline.link"]),
+    ("tag", &["entity.name.tag"]),
+    (
+        "type",
+        &["entity.name.type", "support.type", "support.class"],
+    ),
+    (
+        "type.builtin",
+        &["support.type.primitive", "support.type", "storage.type"],
+    ),
+    (
+        "type.enum.variant",
+        &["variable.other.enummember", "constant.other.enum"],
+    ),
+    ("variable", &["variable"]),
+    ("variable.builtin", &["variable.language"]),
+    (
+        "variable.other.member",
+        &["variable.other.property", "variable.other.member"],
+    ),
+    ("variable.parameter", &["variable.parameter"]),
+    ("markup.bold", &["markup.bold"]),
+    ("markup.heading", &["markup.heading", "entity.name.section"]),
+    ("markup.italic", &["markup.italic"]),
+    ("markup.link.text", &["string.other.link", "markup.link"]),
+    ("markup.link.url", &["markup.underline.link"]),
+    (
+        "markup.list",
+        &["markup.list", "punctuation.definition.list"],
+    ),
+    ("markup.quote", &["markup.quote"]),
+    ("markup.raw", &["markup.raw", "markup.inline.raw"]),
+    ("markup.strikethrough", &["markup.strikethrough"]),
+    ("diff.delta", &["markup.changed"]),
+    ("diff.minus", &["markup.deleted"]),
+    ("diff.plus", &["markup.inserted"]),
+];
+
+/// A TextMate theme rule styling all scopes matched by its selector.
+#[derive(Default)]
+pub(crate) struct Rule {
+    /// Comma-separated scope selector.
+    pub scope: String,
+    pub foreground: Option<String>,
+    pub background: Option<String>,
+    /// Space-separated font styles, an empty string resets inherited font styles.
+    pub font_style: Option<String>,
+}
+
+impl Rule {
+    /// Specificity of the best selector matching `scope`, i.e. the number of its dotted parts.
+    ///
+    /// Descendant and exclusion selectors such as `source.rust string` or `string -comment`
+    /// depend on context that does not exist for capture names and never match.
+    fn specificity(&self, scope: &str) -> Option<usize> {
+        self.scope
+            .split(',')
+            .map(str::trim)
+            .filter(|selector| !selector.is_empty() && !selector.contains(' '))
+            .filter(|selector| {
+                scope
+                    .strip_prefix(selector)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+            .map(|selector| selector.split('.').count())
+            .max()
+    }
+}
+
+/// Style of each capture name of [`TEXTMATE_SCOPES`] matched by any of `rules`.
+///
+/// Like in TextMate, every property is taken from the most specific rule that sets it, with later
+/// rules winning ties. Rules with invalid colors are treated as if they did not set the color, so
+/// a less specific rule with a valid color applies instead. Alpha channels are dropped.
+pub(crate) fn styles(rules: &[Rule]) -> HashMap<String, Style> {
+    let mut styles = HashMap::new();
+
+    for (capture, scopes) in TEXTMATE_SCOPES {
+        let best = |property: fn(&Rule) -> Option<String>| {
+            rules
+                .iter()
+                .enumerate()
+                .filter_map(|(index, rule)| {
+                    let value = property(rule)?;
+
+                    scopes
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(preference, scope)| {
+                            Some((rule.specificity(scope)?, usize::MAX - preference))
+                        })
+                        .max()
+                        .map(|(specificity, preference)| ((specificity, preference, index), value))
+                })
+                .max_by_key(|(key, _)| *key)
+                .map(|(_, value)| value)
+        };
+
+        let mut style = Style {
+            color: best(|rule| rule.foreground.as_deref().and_then(color::opaque)),
+            background: best(|rule| rule.background.as_deref().and_then(color::opaque)),
+            ..Default::default()
+        };
+
+        let font_style = best(|rule| rule.font_style.clone());
+
+        for font_style in font_style
+            .iter()
+            .flat_map(|styles| styles.split_whitespace())
+        {
+            apply_font_style(&mut style, font_style);
+        }
+
+        if style.color.is_some() || style.background.is_some() || font_style.is_some() {
+            styles.insert(capture.to_string(), style);
+        }
+    }
+
+    styles
+}
+
+/// Enable the TextMate font style `name` in `style`.
+pub(crate) fn apply_font_style(style: &mut Style, name: &str) {
+    match name {
+        "bold" => style.is_bold = true,
+        "italic" => style.is_italic = true,
+        "underline" => style.is_underlined = true,
+        "strikethrough" => style.is_crossed_out = true,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(scope: &str, foreground: &str) -> Rule {
+        Rule {
+            scope: scope.to_string(),
+            foreground: Some(foreground.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn most_specific_rule() {
+        let styles = styles(&[
+            rule("keyword", "#111111"),
+            rule("keyword.control", "#222222"),
+            rule("keyword", "#333333"),
+        ]);
+
+        assert_eq!(styles["keyword"].color.as_deref(), Some("#333333"));
+        assert_eq!(styles["keyword.control"].color.as_deref(), Some("#222222"));
+    }
+
+    #[test]
+    fn skip_invalid_colors() {
+        let styles = styles(&[
+            rule("keyword", "#111111"),
+            rule("keyword.control", "not a color"),
+            rule("string", "#22222280"),
+        ]);
+
+        assert_eq!(styles["keyword.control"].color.as_deref(), Some("#111111"));
+        assert_eq!(styles["string"].color.as_deref(), Some("#222222"));
+    }
+}
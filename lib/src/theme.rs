@@ -1,110 +1,936 @@
-use crate::renderer::HIGHLIGHT_NAMES;
+use crate::color::{self, Rgb};
 use crate::Error;
 use std::collections::HashMap;
 use std::convert::From;
+use std::fmt::Write;
+use std::path::Path;
 use toml::value::Table;
 use toml::Value;
 
-pub(crate) struct Style {
-    pub color: String,
+/// Color and text modifiers of a highlighted item.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    /// Foreground color in CSS notation, usually `#rrggbb`.
+    pub color: Option<String>,
+    /// Background color in CSS notation, usually `#rrggbb`.
+    pub background: Option<String>,
+    /// Whether the text is bold.
     pub is_bold: bool,
+    /// Whether the text is dimmed.
+    pub is_dim: bool,
+    /// Whether the text is italic.
     pub is_italic: bool,
+    /// Whether the text is underlined, see also [`Style::underline_style`].
+    pub is_underlined: bool,
+    /// Whether the text blinks slowly.
+    pub is_slow_blink: bool,
+    /// Whether the text blinks rapidly.
+    pub is_rapid_blink: bool,
+    /// Whether foreground and background colors are swapped.
+    pub is_reversed: bool,
+    /// Whether the text is hidden.
+    pub is_hidden: bool,
+    /// Whether the text is crossed out.
+    pub is_crossed_out: bool,
+    /// Shape of the underline if the text is underlined.
+    pub underline_style: UnderlineStyle,
+    /// Underline color in CSS notation if different from the foreground color.
+    pub underline_color: Option<String>,
 }
 
 impl From<&String> for Style {
     fn from(color: &String) -> Self {
         Style {
-            color: color.clone(),
-            is_bold: false,
-            is_italic: false,
+            color: Some(color.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Shape of an underline as set by the `style` of Helix' `underline` table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum UnderlineStyle {
+    /// Single straight line.
+    #[default]
+    Line,
+    /// Curly line.
+    Curl,
+    /// Dashed line.
+    Dashed,
+    /// Dotted line.
+    Dotted,
+    /// Two straight lines.
+    DoubleLine,
+}
+
+impl UnderlineStyle {
+    /// Parse the Helix name of the underline style.
+    fn from_helix(name: &str) -> Option<Self> {
+        match name {
+            "line" => Some(UnderlineStyle::Line),
+            "curl" => Some(UnderlineStyle::Curl),
+            "dashed" => Some(UnderlineStyle::Dashed),
+            "dotted" => Some(UnderlineStyle::Dotted),
+            "double_line" => Some(UnderlineStyle::DoubleLine),
+            _ => None,
         }
     }
 }
 
 /// A theme defining colors and modifiers to be used for syntax highlighting.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Theme {
-    pub(crate) style_map: HashMap<usize, Style>,
-    pub(crate) foreground: Style,
-    pub(crate) background: Style,
+    pub(crate) styles: HashMap<String, Style>,
+    pub(crate) palette: HashMap<String, String>,
+    pub(crate) foreground: String,
+    pub(crate) background: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            styles: HashMap::default(),
+            palette: HashMap::default(),
+            foreground: "#fff".to_string(),
+            background: "#000".to_string(),
+        }
+    }
 }
 
 impl Theme {
     /// Load theme from a Helix [compatible](https://docs.helix-editor.com/themes.html) theme
     /// description stored in `data`.
     ///
+    /// Like in Helix, a capture name is styled by the longest dotted prefix defined in the theme,
+    /// i.e. `function.method` falls back to `function` if the theme does not define the former.
+    ///
+    /// Colors are either `#rrggbb` hex values, entries of the optional `[palette]` table or one of
+    /// the 16 named terminal colors such as `light-red`. Like in Helix, the alpha channel of
+    /// `#rrggbbaa` values is ignored.
+    ///
+    /// A theme declaring `inherits = "name"` is based on the bundled [`themes`](crate::themes)
+    /// of the same name, see [`Theme::from_helix_with()`] to inherit from other themes.
+    ///
     /// # Errors
     ///
     /// If the theme cannot be parsed either because it is not a TOML file or does not adhere to
-    /// the Helix syntax expectations, this function returns an [`Error`].
+    /// the Helix syntax expectations, this function returns an [`Error`]. An unknown color yields
+    /// [`Error::InvalidColor`] naming the offending key, e.g. `palette.red` for a palette entry
+    /// that is neither a hex value nor a named terminal color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Error, Theme};
+    ///
+    /// let theme = Theme::from_helix(r##"
+    /// "keyword" = "#ff79c6"
+    /// "comment" = { fg = "light-red", modifiers = ["italic"] }
+    /// "##);
+    /// assert!(theme.is_ok());
+    ///
+    /// let theme = Theme::from_helix(r#""function" = { fg = "pink" }"#);
+    /// assert!(matches!(theme, Err(Error::InvalidColor { key, .. }) if key == "function.fg"));
+    ///
+    /// let theme = Theme::from_helix(r#"palette = { red = "not a color; }" }"#);
+    /// assert!(matches!(theme, Err(Error::InvalidColor { key, .. }) if key == "palette.red"));
+    /// ```
     pub fn from_helix(data: &str) -> Result<Self, Error> {
-        let root = match data.parse::<toml::Value>()? {
-            Value::Table(table) => table,
-            _ => return Err(Error::InvalidTheme),
+        Self::from_helix_with(data, |_| None)
+    }
+
+    /// Load theme from a Helix theme description stored in `data` and resolve `inherits` with
+    /// `resolver`, which maps a theme name to its description. Names unknown to `resolver` are
+    /// looked up in the bundled [`themes`](crate::themes).
+    ///
+    /// Like in Helix, the inheriting theme replaces the parent's styles key by key and its palette
+    /// is merged into the parent's palette, so parent styles pick up overridden palette colors.
+    ///
+    /// # Errors
+    ///
+    /// Besides the errors of [`Theme::from_helix()`], this function fails with
+    /// [`Error::UnknownTheme`] if an inherited theme cannot be found and with
+    /// [`Error::InheritanceCycle`] if themes inherit from each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Renderer, Theme};
+    ///
+    /// let base = r##"
+    /// "keyword" = "red"
+    /// "function" = "blue"
+    ///
+    /// [palette]
+    /// red = "#ff0000"
+    /// blue = "#0000ff"
+    /// "##;
+    ///
+    /// let derived = r##"
+    /// inherits = "base"
+    /// "function" = { fg = "red", modifiers = ["bold"] }
+    ///
+    /// [palette]
+    /// red = "#cc0000"
+    /// "##;
+    ///
+    /// let theme = Theme::from_helix_with(derived, |name| (name == "base").then(|| base.to_string()))
+    ///     .unwrap();
+    ///
+    /// let css = Renderer::new(theme).css();
+    /// assert!(css.contains("#cc0000"));
+    /// assert!(!css.contains("#ff0000"));
+    /// ```
+    pub fn from_helix_with<F>(data: &str, mut resolver: F) -> Result<Self, Error>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        Self::from_table(flatten(data, &mut resolver, &mut Vec::new())?)
+    }
+
+    /// Load theme from a Helix theme description stored in `data` and resolve `inherits` with
+    /// the `<name>.toml` files in `dir`, e.g. `~/.config/helix/themes`, or the bundled
+    /// [`themes`](crate::themes).
+    ///
+    /// # Errors
+    ///
+    /// See [`Theme::from_helix_with()`].
+    pub fn from_helix_dir<P: AsRef<Path>>(data: &str, dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref();
+
+        Self::from_helix_with(data, |name| {
+            std::fs::read_to_string(dir.join(format!("{name}.toml"))).ok()
+        })
+    }
+
+    /// Build theme from a TOML table without inheritance.
+    fn from_table(root: Table) -> Result<Self, Error> {
+        let invalid = |key: &str, value: &Value| Error::InvalidColor {
+            key: key.to_string(),
+            color: value
+                .as_str()
+                .map_or_else(|| value.type_str().to_string(), String::from),
         };
 
-        let palette = root.get("palette").ok_or(Error::InvalidTheme)?;
+        let palette: HashMap<String, String> = match root.get("palette") {
+            None => HashMap::default(),
+            Some(Value::Table(palette)) => palette
+                .iter()
+                .map(|(name, value)| {
+                    let color = value
+                        .as_str()
+                        .and_then(palette_entry)
+                        .ok_or_else(|| invalid(&format!("palette.{name}"), value))?;
 
-        let referenced_color = |table: &Table, name: &str| -> Result<Style, Error> {
-            if let Some(Value::String(reference)) = table.get(name) {
-                if let Some(Value::String(color)) = palette.get(reference) {
-                    return Ok(Style::from(color));
-                }
-            }
+                    Ok((name.clone(), color))
+                })
+                .collect::<Result<_, Error>>()?,
+            Some(_) => return Err(Error::InvalidTheme),
+        };
 
-            Err(Error::InvalidColorReference(name.to_string()))
+        let color = |key: &str, value: &Value| -> Result<String, Error> {
+            value
+                .as_str()
+                .and_then(|name| lookup_color(&palette, name))
+                .ok_or_else(|| invalid(key, value))
         };
 
-        let fg_color = |name: &str| -> Result<Option<Style>, Error> {
-            if let Some(value) = root.get(name) {
-                match value {
-                    Value::String(reference) => {
-                        if let Some(Value::String(color)) = palette.get(reference) {
-                            return Ok(Some(Style::from(color)));
-                        }
-                    }
-                    Value::Table(table) => {
-                        let mut style = referenced_color(table, "fg")?;
-
-                        if let Some(Value::Array(modifiers)) = table.get("modifiers") {
-                            for modifier in modifiers {
-                                if let Value::String(modifier) = modifier {
-                                    if modifier == "italic" {
-                                        style.is_italic = true;
-                                    } else if modifier == "bold" {
-                                        style.is_bold = true;
-                                    }
-                                }
+        let field = |key: &str, table: &Table, field: &str| -> Result<Option<String>, Error> {
+            table
+                .get(field)
+                .map(|value| color(&format!("{key}.{field}"), value))
+                .transpose()
+        };
+
+        let style = |key: &str, value: &Value| -> Result<Option<Style>, Error> {
+            match value {
+                Value::String(_) => Ok(Some(Style {
+                    color: Some(color(key, value)?),
+                    ..Default::default()
+                })),
+                Value::Table(table) => {
+                    let mut style = Style {
+                        color: field(key, table, "fg")?,
+                        background: field(key, table, "bg")?,
+                        ..Default::default()
+                    };
+
+                    if let Some(Value::Array(modifiers)) = table.get("modifiers") {
+                        for modifier in modifiers {
+                            match modifier.as_str() {
+                                Some("bold") => style.is_bold = true,
+                                Some("dim") => style.is_dim = true,
+                                Some("italic") => style.is_italic = true,
+                                Some("underlined") => style.is_underlined = true,
+                                Some("slow_blink") => style.is_slow_blink = true,
+                                Some("rapid_blink") => style.is_rapid_blink = true,
+                                Some("reversed") => style.is_reversed = true,
+                                Some("hidden") => style.is_hidden = true,
+                                Some("crossed_out") => style.is_crossed_out = true,
+                                _ => {}
                             }
                         }
+                    }
+
+                    if let Some(Value::Table(underline)) = table.get("underline") {
+                        style.underline_color =
+                            field(&format!("{key}.underline"), underline, "color")?;
 
-                        return Ok(Some(style));
+                        if let Some(shape) = underline
+                            .get("style")
+                            .and_then(Value::as_str)
+                            .and_then(UnderlineStyle::from_helix)
+                        {
+                            style.is_underlined = true;
+                            style.underline_style = shape;
+                        }
                     }
-                    _ => {}
+
+                    Ok(Some(style))
                 }
+                _ => Ok(None),
             }
-
-            Ok(None)
         };
 
-        let mut style_map = HashMap::default();
+        let mut styles = HashMap::default();
+
+        for (name, value) in &root {
+            if name == "palette" || name.starts_with("ui.") {
+                continue;
+            }
 
-        for (index, name) in HIGHLIGHT_NAMES.iter().enumerate() {
-            if let Some(style) = fg_color(*name)? {
-                style_map.insert(index, style);
+            if let Some(style) = style(name, value)? {
+                styles.insert(name.clone(), style);
             }
         }
 
         let background = match root.get("ui.background") {
-            Some(Value::Table(table)) => referenced_color(table, "bg")?,
-            _ => Style::from(&"#000".to_string()),
+            Some(Value::Table(table)) => field("ui.background", table, "bg")?,
+            _ => None,
         };
 
-        let foreground = fg_color("ui.text")?.unwrap_or_else(|| Style::from(&"#fff".to_string()));
+        let foreground = match root.get("ui.text") {
+            Some(value) => style("ui.text", value)?.and_then(|style| style.color),
+            None => None,
+        };
+
+        let defaults = Self::default();
 
         Ok(Self {
-            style_map,
-            foreground,
-            background,
+            styles,
+            palette,
+            foreground: foreground.unwrap_or(defaults.foreground),
+            background: background.unwrap_or(defaults.background),
         })
     }
+
+    /// Create a builder to construct a theme in code.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Style, Theme};
+    ///
+    /// let theme = Theme::builder()
+    ///     .palette("brand", "#e4007c")
+    ///     .background("#101010")
+    ///     .style("keyword", Style { color: Some("brand".into()), is_bold: true, ..Default::default() })
+    ///     .style("comment", Style { color: Some("gray".into()), ..Default::default() })
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(theme.style("keyword").unwrap().color.as_deref(), Some("#e4007c"));
+    /// assert_eq!(theme.resolve("keyword.control").unwrap().color.as_deref(), Some("#e4007c"));
+    /// assert_eq!(theme.background(), "#101010");
+    /// ```
+    pub fn builder() -> ThemeBuilder {
+        ThemeBuilder {
+            theme: Self::default(),
+        }
+    }
+
+    /// Style defined for exactly `scope`.
+    pub fn style(&self, scope: &str) -> Option<&Style> {
+        self.styles.get(scope)
+    }
+
+    /// Look up the style of `scope` or, if not defined, of its closest parent scope.
+    pub fn resolve(&self, scope: &str) -> Option<&Style> {
+        scope_fallbacks(scope).find_map(|scope| self.styles.get(scope))
+    }
+
+    /// Define the style of `scope` and return the style previously defined for it. Colors must be
+    /// hex values, they are not looked up in the palette.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidColor`] naming the Helix key of a color that is not a hex value.
+    pub fn set_style<S: Into<String>>(
+        &mut self,
+        scope: S,
+        style: Style,
+    ) -> Result<Option<Style>, Error> {
+        let scope = scope.into();
+
+        for (field, color) in [
+            ("fg", &style.color),
+            ("bg", &style.background),
+            ("underline.color", &style.underline_color),
+        ] {
+            if let Some(color) = color {
+                check_hex(&format!("{scope}.{field}"), color)?;
+            }
+        }
+
+        Ok(self.styles.insert(scope, style))
+    }
+
+    /// Remove the style of `scope` and return it, so that the scope falls back to its parent.
+    pub fn remove_style(&mut self, scope: &str) -> Option<Style> {
+        self.styles.remove(scope)
+    }
+
+    /// Iterate over all scopes defined by the theme and their styles in arbitrary order.
+    pub fn scopes(&self) -> impl Iterator<Item = (&str, &Style)> {
+        self.styles
+            .iter()
+            .map(|(scope, style)| (scope.as_str(), style))
+    }
+
+    /// Color of palette entry `name`.
+    pub fn palette_color(&self, name: &str) -> Option<&str> {
+        self.palette.get(name).map(String::as_str)
+    }
+
+    /// Iterate over all palette entries and their colors in arbitrary order.
+    pub fn palette(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.palette
+            .iter()
+            .map(|(name, color)| (name.as_str(), color.as_str()))
+    }
+
+    /// Default text color of the theme.
+    pub fn foreground(&self) -> &str {
+        &self.foreground
+    }
+
+    /// Set the default text color as hex value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidColor`] if `color` is not a hex value.
+    pub fn set_foreground<S: Into<String>>(&mut self, color: S) -> Result<(), Error> {
+        let color = color.into();
+        check_hex("ui.text.fg", &color)?;
+        self.foreground = color;
+        Ok(())
+    }
+
+    /// Background color of the theme.
+    pub fn background(&self) -> &str {
+        &self.background
+    }
+
+    /// Set the background color as hex value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidColor`] if `color` is not a hex value.
+    pub fn set_background<S: Into<String>>(&mut self, color: S) -> Result<(), Error> {
+        let color = color.into();
+        check_hex("ui.background.bg", &color)?;
+        self.background = color;
+        Ok(())
+    }
+
+    /// Export the theme as normalized Helix TOML document.
+    ///
+    /// Scopes and palette entries are sorted by name and inherited styles are included, so the
+    /// document stands on its own. Colors defined in the palette are referenced by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::Theme;
+    ///
+    /// let theme = Theme::from_helix(tree_painter::themes::CATPPUCCIN_MOCHA).unwrap();
+    /// let data = theme.to_helix();
+    ///
+    /// assert!(data.contains(r#""keyword" = { fg = "mauve" }"#));
+    /// assert_eq!(Theme::from_helix(&data).unwrap(), theme);
+    /// ```
+    pub fn to_helix(&self) -> String {
+        let mut palette = self.palette.iter().collect::<Vec<_>>();
+        palette.sort();
+
+        let color = |color: &str| {
+            let name = palette
+                .iter()
+                .find(|(_, value)| value.as_str() == color)
+                .map_or(color, |(name, _)| name.as_str());
+
+            toml_string(name)
+        };
+
+        let mut scopes = self.styles.iter().collect::<Vec<_>>();
+        scopes.sort_by_key(|(scope, _)| scope.as_str());
+
+        let mut data = String::new();
+
+        for (scope, style) in scopes {
+            let mut fields = Vec::new();
+
+            if let Some(fg) = &style.color {
+                fields.push(format!("fg = {}", color(fg)));
+            }
+
+            if let Some(bg) = &style.background {
+                fields.push(format!("bg = {}", color(bg)));
+            }
+
+            let plain_underline =
+                style.underline_style == UnderlineStyle::Line && style.underline_color.is_none();
+
+            let modifiers = [
+                (style.is_bold, "bold"),
+                (style.is_dim, "dim"),
+                (style.is_italic, "italic"),
+                (style.is_underlined && plain_underline, "underlined"),
+                (style.is_slow_blink, "slow_blink"),
+                (style.is_rapid_blink, "rapid_blink"),
+                (style.is_reversed, "reversed"),
+                (style.is_hidden, "hidden"),
+                (style.is_crossed_out, "crossed_out"),
+            ]
+            .iter()
+            .filter(|(enabled, _)| *enabled)
+            .map(|(_, name)| toml_string(name))
+            .collect::<Vec<_>>();
+
+            if !modifiers.is_empty() {
+                fields.push(format!("modifiers = [{}]", modifiers.join(", ")));
+            }
+
+            let mut underline = Vec::new();
+
+            if let Some(underline_color) = &style.underline_color {
+                underline.push(format!("color = {}", color(underline_color)));
+            }
+
+            if style.is_underlined && !plain_underline {
+                let shape = match style.underline_style {
+                    UnderlineStyle::Line => "line",
+                    UnderlineStyle::Curl => "curl",
+                    UnderlineStyle::Dashed => "dashed",
+                    UnderlineStyle::Dotted => "dotted",
+                    UnderlineStyle::DoubleLine => "double_line",
+                };
+
+                underline.push(format!("style = {}", toml_string(shape)));
+            }
+
+            if !underline.is_empty() {
+                fields.push(format!("underline = {{ {} }}", underline.join(", ")));
+            }
+
+            let _ = writeln!(data, "{} = {{ {} }}", toml_string(scope), fields.join(", "));
+        }
+
+        let _ = writeln!(
+            data,
+            "\"ui.background\" = {{ bg = {} }}",
+            color(&self.background)
+        );
+        let _ = writeln!(data, "\"ui.text\" = {{ fg = {} }}", color(&self.foreground));
+
+        if !palette.is_empty() {
+            data.push_str("\n[palette]\n");
+
+            for (name, value) in palette {
+                let _ = writeln!(data, "{} = {}", toml_key(name), toml_string(value));
+            }
+        }
+
+        data
+    }
+}
+
+/// Builder to construct a [`Theme`] in code.
+///
+/// Colors are given like in Helix themes, i.e. as hex values, palette entries or named terminal
+/// colors, and resolved by [`ThemeBuilder::build()`].
+#[derive(Clone, Debug)]
+pub struct ThemeBuilder {
+    theme: Theme,
+}
+
+impl ThemeBuilder {
+    /// Define palette entry `name` with a hex `color` or a named terminal color.
+    pub fn palette<N: Into<String>, C: Into<String>>(mut self, name: N, color: C) -> Self {
+        self.theme.palette.insert(name.into(), color.into());
+        self
+    }
+
+    /// Set the default text color.
+    pub fn foreground<S: Into<String>>(mut self, color: S) -> Self {
+        self.theme.foreground = color.into();
+        self
+    }
+
+    /// Set the background color.
+    pub fn background<S: Into<String>>(mut self, color: S) -> Self {
+        self.theme.background = color.into();
+        self
+    }
+
+    /// Define the style of `scope`.
+    pub fn style<S: Into<String>>(mut self, scope: S, style: Style) -> Self {
+        self.theme.styles.insert(scope.into(), style);
+        self
+    }
+
+    /// Resolve all colors and build the theme.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidColor`] naming the Helix key of a color that is neither a hex
+    /// value, a palette entry nor a named terminal color, e.g. `palette.red` for an invalid
+    /// palette entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tree_painter::{Error, Style, Theme};
+    ///
+    /// let theme = Theme::builder()
+    ///     .palette("red", "bogus")
+    ///     .style("keyword", Style { color: Some("red".into()), ..Default::default() })
+    ///     .build();
+    ///
+    /// assert!(matches!(theme, Err(Error::InvalidColor { key, .. }) if key == "palette.red"));
+    /// ```
+    pub fn build(self) -> Result<Theme, Error> {
+        let mut theme = self.theme;
+
+        for (name, color) in theme.palette.iter_mut() {
+            *color = palette_entry(color).ok_or_else(|| Error::InvalidColor {
+                key: format!("palette.{name}"),
+                color: color.clone(),
+            })?;
+        }
+
+        let palette = &theme.palette;
+
+        let resolve = |key: String, color: &mut String| -> Result<(), Error> {
+            *color = lookup_color(palette, color).ok_or_else(|| Error::InvalidColor {
+                key,
+                color: color.clone(),
+            })?;
+            Ok(())
+        };
+
+        for (scope, style) in theme.styles.iter_mut() {
+            if let Some(color) = &mut style.color {
+                resolve(format!("{scope}.fg"), color)?;
+            }
+
+            if let Some(color) = &mut style.background {
+                resolve(format!("{scope}.bg"), color)?;
+            }
+
+            if let Some(color) = &mut style.underline_color {
+                resolve(format!("{scope}.underline.color"), color)?;
+            }
+        }
+
+        resolve("ui.text.fg".to_string(), &mut theme.foreground)?;
+        resolve("ui.background.bg".to_string(), &mut theme.background)?;
+
+        Ok(theme)
+    }
+}
+
+/// Quote `value` as TOML basic string.
+fn toml_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04X}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// TOML key for `name`, quoted only if it is not a valid bare key.
+fn toml_key(name: &str) -> String {
+    let bare = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if bare {
+        name.to_string()
+    } else {
+        toml_string(name)
+    }
+}
+
+/// Resolve color `name` as hex value, palette entry or named terminal color, preferring them in
+/// this order like Helix.
+fn lookup_color(palette: &HashMap<String, String>, name: &str) -> Option<String> {
+    if name.starts_with('#') {
+        return color::opaque(name);
+    }
+
+    match palette.get(name) {
+        Some(color) => palette_entry(color),
+        None => color::named(name).map(String::from),
+    }
+}
+
+/// Fail with [`Error::InvalidColor`] for `key` unless `color` is a hex value.
+fn check_hex(key: &str, color: &str) -> Result<(), Error> {
+    match Rgb::from_hex(color) {
+        Some(_) => Ok(()),
+        None => Err(Error::InvalidColor {
+            key: key.to_string(),
+            color: color.to_string(),
+        }),
+    }
+}
+
+/// Resolve the color of a palette entry, which is either a hex value or a named terminal color.
+fn palette_entry(color: &str) -> Option<String> {
+    if color.starts_with('#') {
+        color::opaque(color)
+    } else {
+        color::named(color).map(String::from)
+    }
+}
+
+/// Parse the Helix theme in `data` and merge it over the themes it inherits from. `chain` holds
+/// the names of the themes inheriting from `data` to detect cycles.
+fn flatten<F>(data: &str, resolver: &mut F, chain: &mut Vec<String>) -> Result<Table, Error>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut table = match data.parse::<Value>()? {
+        Value::Table(table) => table,
+        _ => return Err(Error::InvalidTheme),
+    };
+
+    let parent = match table.remove("inherits") {
+        None => return Ok(table),
+        Some(Value::String(parent)) => parent,
+        Some(_) => return Err(Error::InvalidTheme),
+    };
+
+    if chain.contains(&parent) {
+        return Err(Error::InheritanceCycle(parent));
+    }
+
+    let data = resolver(&parent)
+        .or_else(|| bundled(&parent).map(String::from))
+        .ok_or_else(|| Error::UnknownTheme(parent.clone()))?;
+
+    chain.push(parent);
+    let mut base = flatten(&data, resolver, chain)?;
+    chain.pop();
+
+    for (key, value) in table {
+        if let (Some(Value::Table(palette)), Value::Table(colors)) = (base.get_mut(&key), &value) {
+            if key == "palette" {
+                palette.extend(colors.clone());
+                continue;
+            }
+        }
+
+        base.insert(key, value);
+    }
+
+    Ok(base)
+}
+
+/// Bundled theme description named like the Helix theme.
+#[cfg(feature = "themes")]
+fn bundled(name: &str) -> Option<&'static str> {
+    use crate::themes::*;
+
+    match name {
+        "ayu_dark" => Some(AYU_DARK),
+        "ayu_light" => Some(AYU_LIGHT),
+        "ayu_mirage" => Some(AYU_MIRAGE),
+        "catppuccin_frappe" => Some(CATPPUCCIN_FRAPPE),
+        "catppuccin_latte" => Some(CATPPUCCIN_LATTE),
+        "catppuccin_macchiato" => Some(CATPPUCCIN_MACCHIATO),
+        "catppuccin_mocha" => Some(CATPPUCCIN_MOCHA),
+        _ => None,
+    }
+}
+
+/// Bundled theme description named like the Helix theme.
+#[cfg(not(feature = "themes"))]
+fn bundled(_name: &str) -> Option<&'static str> {
+    None
+}
+
+/// Iterate over `scope` and its parents from the most to the least specific one, e.g.
+/// `function.method.call`, `function.method` and `function`.
+pub(crate) fn scope_fallbacks(scope: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(scope), |scope| {
+        scope.rsplit_once('.').map(|(parent, _)| parent)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Create a fresh theme directory containing `<name>.toml` files for the given themes.
+    fn theme_dir(name: &str, themes: &[(&str, &str)]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tree-painter-{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        for (theme, data) in themes {
+            std::fs::write(dir.join(format!("{theme}.toml")), data).unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn helix_round_trip() {
+        let theme = Theme::builder()
+            .palette("dark gray", "#282828")
+            .palette("brand.pink", "#e4007c")
+            .palette("warn", "light-yellow")
+            .foreground("#eeeeee")
+            .background("dark gray")
+            .style(
+                "keyword",
+                Style {
+                    color: Some("brand.pink".into()),
+                    is_bold: true,
+                    ..Default::default()
+                },
+            )
+            .style(
+                "diagnostic.warning",
+                Style {
+                    is_underlined: true,
+                    underline_style: UnderlineStyle::Curl,
+                    underline_color: Some("warn".into()),
+                    ..Default::default()
+                },
+            )
+            .style(
+                "markup.link.\"url\"",
+                Style {
+                    color: Some("#0000ff".into()),
+                    background: Some("dark gray".into()),
+                    is_underlined: true,
+                    ..Default::default()
+                },
+            )
+            .build()
+            .unwrap();
+
+        let data = theme.to_helix();
+        assert!(data.contains(r##""dark gray" = "#282828""##));
+        assert!(data.contains(r#"underline = { color = "warn", style = "curl" }"#));
+        assert!(data.contains(r#""ui.background" = { bg = "dark gray" }"#));
+        assert_eq!(Theme::from_helix(&data).unwrap(), theme);
+    }
+
+    #[test]
+    fn multi_level_inherits() {
+        let dir = theme_dir(
+            "multi-level-inherits",
+            &[
+                (
+                    "base",
+                    r##"
+                    "keyword" = "red"
+                    "function" = "blue"
+                    "comment" = { fg = "gray", modifiers = ["italic"] }
+                    "ui.background" = { bg = "#000000" }
+
+                    [palette]
+                    red = "#ff0000"
+                    blue = "#0000ff"
+                    gray = "#808080"
+                    "##,
+                ),
+                (
+                    "middle",
+                    r##"
+                    inherits = "base"
+                    "function" = { fg = "red", modifiers = ["bold"] }
+
+                    [palette]
+                    red = "#cc0000"
+                    "##,
+                ),
+            ],
+        );
+
+        let theme = Theme::from_helix_dir(
+            r##"
+            inherits = "middle"
+            "comment" = "blue"
+
+            [palette]
+            blue = "#0000cc"
+            "##,
+            &dir,
+        )
+        .unwrap();
+
+        assert_eq!(
+            theme.style("keyword").unwrap().color.as_deref(),
+            Some("#cc0000")
+        );
+
+        let function = theme.style("function").unwrap();
+        assert_eq!(function.color.as_deref(), Some("#cc0000"));
+        assert!(function.is_bold);
+
+        let comment = theme.style("comment").unwrap();
+        assert_eq!(comment.color.as_deref(), Some("#0000cc"));
+        assert!(!comment.is_italic);
+
+        assert_eq!(theme.background(), "#000000");
+        assert_eq!(theme.palette_color("gray"), Some("#808080"));
+    }
+
+    #[test]
+    fn inheritance_cycle() {
+        let dir = theme_dir(
+            "inheritance-cycle",
+            &[
+                ("a", r#"inherits = "b""#),
+                ("b", r#"inherits = "c""#),
+                ("c", r#"inherits = "a""#),
+            ],
+        );
+
+        let theme = Theme::from_helix_dir(r#"inherits = "a""#, &dir);
+        assert!(matches!(theme, Err(Error::InheritanceCycle(name)) if name == "a"));
+
+        let theme = Theme::from_helix_dir(r#"inherits = "missing""#, &dir);
+        assert!(matches!(theme, Err(Error::UnknownTheme(name)) if name == "missing"));
+    }
 }
@@ -0,0 +1,108 @@
+use crate::color;
+use crate::textmate::{self, Rule};
+use crate::{Error, Theme};
+use plist::{Dictionary, Value};
+
+impl Theme {
+    /// Load theme from a TextMate or Sublime Text `.tmTheme` property list stored in `data`.
+    ///
+    /// The global settings without scope set the theme's foreground and background. The TextMate
+    /// scopes of all other settings are mapped to capture names, taking every color and font style
+    /// from the most specific selector matching one of the scopes listed for a capture name in
+    /// [`TEXTMATE_SCOPES`](crate::TEXTMATE_SCOPES). Selectors that depend on context, e.g.
+    /// `source.rust string` or `string -comment`, are ignored, as are invalid colors. Alpha
+    /// channels of `#rrggbbaa` colors are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Plist`] if `data` is not a valid property list and
+    /// [`Error::InvalidTheme`] if it does not contain a `settings` array.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = r##"<?xml version="1.0" encoding="UTF-8"?>
+    /// <plist version="1.0">
+    /// <dict>
+    ///     <key>settings</key>
+    ///     <array>
+    ///         <dict>
+    ///             <key>settings</key>
+    ///             <dict>
+    ///                 <key>background</key><string>#272822</string>
+    ///                 <key>foreground</key><string>#F8F8F2</string>
+    ///             </dict>
+    ///         </dict>
+    ///         <dict>
+    ///             <key>scope</key><string>keyword, storage</string>
+    ///             <key>settings</key>
+    ///             <dict><key>foreground</key><string>#F92672</string></dict>
+    ///         </dict>
+    ///         <dict>
+    ///             <key>scope</key><string>comment</string>
+    ///             <key>settings</key>
+    ///             <dict>
+    ///                 <key>foreground</key><string>#75715E</string>
+    ///                 <key>fontStyle</key><string>italic</string>
+    ///             </dict>
+    ///         </dict>
+    ///     </array>
+    /// </dict>
+    /// </plist>"##;
+    ///
+    /// let theme = tree_painter::Theme::from_tmtheme(data).unwrap();
+    ///
+    /// assert_eq!(theme.background(), "#272822");
+    /// assert_eq!(theme.style("keyword.storage.type").unwrap().color.as_deref(), Some("#F92672"));
+    /// assert!(theme.style("comment").unwrap().is_italic);
+    /// ```
+    pub fn from_tmtheme(data: &str) -> Result<Self, Error> {
+        let root = Value::from_reader(std::io::Cursor::new(data.as_bytes()))?;
+
+        let settings = root
+            .as_dictionary()
+            .and_then(|root| root.get("settings"))
+            .and_then(Value::as_array)
+            .ok_or(Error::InvalidTheme)?;
+
+        let mut theme = Theme::default();
+        let mut rules = Vec::new();
+
+        for item in settings.iter().filter_map(Value::as_dictionary) {
+            let Some(settings) = item.get("settings").and_then(Value::as_dictionary) else {
+                continue;
+            };
+
+            match item.get("scope").and_then(Value::as_string) {
+                Some(scope) => rules.push(Rule {
+                    scope: scope.to_string(),
+                    foreground: string(settings, "foreground"),
+                    background: string(settings, "background"),
+                    font_style: string(settings, "fontStyle"),
+                }),
+                None => {
+                    let color = |key| string(settings, key).as_deref().and_then(color::opaque);
+
+                    if let Some(color) = color("foreground") {
+                        theme.foreground = color;
+                    }
+
+                    if let Some(color) = color("background") {
+                        theme.background = color;
+                    }
+                }
+            }
+        }
+
+        theme.styles = textmate::styles(&rules);
+        Ok(theme)
+    }
+}
+
+/// String value of `key` in `dictionary`.
+fn string(dictionary: &Dictionary, key: &str) -> Option<String> {
+    dictionary
+        .get(key)
+        .and_then(Value::as_string)
+        .map(String::from)
+}
@@ -0,0 +1,71 @@
+use crate::theme::Style;
+use std::ops::Range;
+use tree_sitter_highlight::{Highlight, HighlightEvent};
+
+/// A piece of highlighted source code that does not span multiple lines.
+///
+/// Tokens returned by [`Renderer::tokens()`](crate::Renderer::tokens) cover the entire source
+/// without gaps, a line break belongs to the token preceding it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<'a> {
+    /// Byte range of the token in the source.
+    pub range: Range<usize>,
+    /// Zero-based line number.
+    pub line: usize,
+    /// Zero-based byte offset from the start of the line.
+    pub column: usize,
+    /// Recognized name of the innermost capture or [`None`] if the token is not highlighted.
+    pub capture: Option<&'a str>,
+    /// Style of the innermost capture that is styled by the theme.
+    pub style: Option<&'a Style>,
+}
+
+/// Split the highlighted regions of `events` into line-wise tokens.
+pub(crate) fn collect<'a>(
+    events: &[HighlightEvent],
+    source: &[u8],
+    capture: impl Fn(Highlight) -> (&'a str, Option<&'a Style>),
+) -> Vec<Token<'a>> {
+    let mut tokens = Vec::new();
+    let mut stack = Vec::new();
+    let mut line = 0;
+    let mut line_start = 0;
+
+    for event in events {
+        match *event {
+            HighlightEvent::HighlightStart(highlight) => stack.push(capture(highlight)),
+            HighlightEvent::HighlightEnd => {
+                stack.pop();
+            }
+            HighlightEvent::Source { start, end } => {
+                let name = stack.last().map(|(name, _)| *name);
+                let style = stack.iter().rev().find_map(|(_, style)| *style);
+                let mut start = start;
+
+                while start < end {
+                    let end = source[start..end]
+                        .iter()
+                        .position(|c| *c == b'\n')
+                        .map_or(end, |position| start + position + 1);
+
+                    tokens.push(Token {
+                        range: start..end,
+                        line,
+                        column: start - line_start,
+                        capture: name,
+                        style,
+                    });
+
+                    if source[end - 1] == b'\n' {
+                        line += 1;
+                        line_start = end;
+                    }
+
+                    start = end;
+                }
+            }
+        }
+    }
+
+    tokens
+}
@@ -0,0 +1,276 @@
+use crate::color;
+use crate::textmate::{self, Rule};
+use crate::{Error, Style, Theme};
+use serde_json::Value;
+
+/// Capture names of the standard semantic token types of VS Code.
+const SEMANTIC_TOKENS: &[(&str, &str)] = &[
+    ("class", "type"),
+    ("comment", "comment"),
+    ("decorator", "attribute"),
+    ("enum", "type"),
+    ("enumMember", "type.enum.variant"),
+    ("function", "function"),
+    ("interface", "type"),
+    ("keyword", "keyword"),
+    ("label", "label"),
+    ("macro", "function.macro"),
+    ("method", "function.method"),
+    ("namespace", "namespace"),
+    ("number", "constant.numeric"),
+    ("operator", "operator"),
+    ("parameter", "variable.parameter"),
+    ("property", "variable.other.member"),
+    ("regexp", "string.regexp"),
+    ("string", "string"),
+    ("struct", "type"),
+    ("type", "type"),
+    ("typeParameter", "type"),
+    ("variable", "variable"),
+];
+
+impl Theme {
+    /// Load theme from a VS Code color theme stored in `data`, which may contain comments and
+    /// trailing commas.
+    ///
+    /// The TextMate scopes of `tokenColors` are mapped to capture names according to
+    /// [`TEXTMATE_SCOPES`](crate::TEXTMATE_SCOPES), e.g. `entity.name.function` to `function`.
+    /// Standard `semanticTokenColors` types without modifiers override the properties they set in
+    /// these styles. `editor.foreground` and `editor.background` of `colors` set the theme's
+    /// foreground and background. Invalid colors are ignored, alpha channels of `#rrggbbaa` colors
+    /// are dropped and themes referenced by `include` are not loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Json`] if `data` is not valid JSON and [`Error::InvalidTheme`] if it does
+    /// not contain a JSON object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let data = r##"{
+    ///     // Comments are allowed.
+    ///     "colors": { "editor.background": "#1e1e1eff", "editor.foreground": "#d4d4d4" },
+    ///     "tokenColors": [
+    ///         { "scope": ["keyword", "storage"], "settings": { "foreground": "#569cd6" } },
+    ///         { "scope": "entity.name.function", "settings": { "foreground": "#dcdcaa" } },
+    ///     ],
+    ///     "semanticTokenColors": { "parameter": { "foreground": "#9cdcfe", "italic": true } },
+    /// }"##;
+    ///
+    /// let theme = tree_painter::Theme::from_vscode(data).unwrap();
+    ///
+    /// assert_eq!(theme.background(), "#1e1e1e");
+    /// assert_eq!(theme.resolve("keyword.control").unwrap().color.as_deref(), Some("#569cd6"));
+    /// assert_eq!(theme.style("function").unwrap().color.as_deref(), Some("#dcdcaa"));
+    /// assert!(theme.style("variable.parameter").unwrap().is_italic);
+    /// ```
+    pub fn from_vscode(data: &str) -> Result<Self, Error> {
+        let root = serde_json::from_str::<Value>(&strip_jsonc(data))?;
+        let root = root.as_object().ok_or(Error::InvalidTheme)?;
+
+        let rules = root
+            .get("tokenColors")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(rule)
+            .collect::<Vec<_>>();
+
+        let mut theme = Theme {
+            styles: textmate::styles(&rules),
+            ..Default::default()
+        };
+
+        // Rules without scope define the defaults in themes converted from TextMate.
+        for rule in rules.iter().filter(|rule| rule.scope.is_empty()) {
+            if let Some(color) = rule.foreground.as_deref().and_then(color::opaque) {
+                theme.foreground = color;
+            }
+
+            if let Some(color) = rule.background.as_deref().and_then(color::opaque) {
+                theme.background = color;
+            }
+        }
+
+        if let Some(colors) = root.get("colors") {
+            let color = |key| {
+                colors
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .and_then(color::opaque)
+            };
+
+            if let Some(color) = color("editor.foreground") {
+                theme.foreground = color;
+            }
+
+            if let Some(color) = color("editor.background") {
+                theme.background = color;
+            }
+        }
+
+        if let Some(Value::Object(tokens)) = root.get("semanticTokenColors") {
+            for (token, capture) in SEMANTIC_TOKENS {
+                let Some(value) = tokens.get(*token) else {
+                    continue;
+                };
+
+                let base = theme.styles.get(*capture).cloned().unwrap_or_default();
+
+                if let Some(style) = semantic_style(value, base) {
+                    theme.styles.insert(capture.to_string(), style);
+                }
+            }
+        }
+
+        Ok(theme)
+    }
+}
+
+/// TextMate rule of a `tokenColors` entry.
+fn rule(value: &Value) -> Option<Rule> {
+    let settings = value.get("settings")?;
+    let string = |key| settings.get(key).and_then(Value::as_str).map(String::from);
+
+    let scope = match value.get("scope") {
+        Some(Value::String(scope)) => scope.clone(),
+        Some(Value::Array(scopes)) => scopes
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(","),
+        _ => String::new(),
+    };
+
+    Some(Rule {
+        scope,
+        foreground: string("foreground"),
+        background: string("background"),
+        font_style: string("fontStyle"),
+    })
+}
+
+/// `base` style with the properties set by a `semanticTokenColors` value applied, which is either
+/// a color or an object with `foreground`, `fontStyle` or boolean font style properties.
+fn semantic_style(value: &Value, mut style: Style) -> Option<Style> {
+    match value {
+        Value::String(color) => {
+            style.color = Some(color::opaque(color)?);
+            Some(style)
+        }
+        Value::Object(settings) => {
+            if let Some(color) = settings
+                .get("foreground")
+                .and_then(Value::as_str)
+                .and_then(color::opaque)
+            {
+                style.color = Some(color);
+            }
+
+            if let Some(font_style) = settings.get("fontStyle").and_then(Value::as_str) {
+                for name in font_style.split_whitespace() {
+                    textmate::apply_font_style(&mut style, name);
+                }
+            }
+
+            for name in ["bold", "italic", "underline", "strikethrough"] {
+                if settings.get(name).and_then(Value::as_bool) == Some(true) {
+                    textmate::apply_font_style(&mut style, name);
+                }
+            }
+
+            Some(style)
+        }
+        _ => None,
+    }
+}
+
+/// Remove comments and trailing commas from JSON with comments as accepted by VS Code.
+fn strip_jsonc(data: &str) -> String {
+    let mut stripped = String::with_capacity(data.len());
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                stripped.push(c);
+
+                while let Some(c) = chars.next() {
+                    stripped.push(c);
+
+                    match c {
+                        '\\' => stripped.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push(c);
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+
+                for c in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+
+                    previous = Some(c);
+                }
+
+                stripped.push(' ');
+            }
+            '}' | ']' => {
+                let end = stripped.trim_end().len();
+
+                if stripped[..end].ends_with(',') {
+                    stripped.remove(end - 1);
+                }
+
+                stripped.push(c);
+            }
+            c => stripped.push(c),
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_semantic_tokens() {
+        let theme = Theme::from_vscode(
+            r##"{
+                "tokenColors": [
+                    { "scope": "keyword", "settings": { "foreground": "#569cd6", "fontStyle": "bold" } },
+                    { "scope": "entity.name.function", "settings": { "foreground": "#dcdcaa", "fontStyle": "italic" } },
+                ],
+                "semanticTokenColors": {
+                    "keyword": { "italic": true },
+                    "function": "#ffcc00",
+                },
+            }"##,
+        )
+        .unwrap();
+
+        let keyword = theme.style("keyword").unwrap();
+        assert_eq!(keyword.color.as_deref(), Some("#569cd6"));
+        assert!(keyword.is_bold);
+        assert!(keyword.is_italic);
+
+        let function = theme.style("function").unwrap();
+        assert_eq!(function.color.as_deref(), Some("#ffcc00"));
+        assert!(function.is_italic);
+    }
+}